@@ -0,0 +1,174 @@
+use select::{document::Document, predicate::*};
+
+use super::{error::{OrParseError, VozError}, models::{ContentType, Inline, Post, TableCell}, parse_utils::parse_post_contents};
+
+/// Font sizes XenForo renders for `[SIZE=1]` to `[SIZE=7]`.
+pub const FONT_SIZES: [&str; 7] = ["9px", "10px", "12px", "15px", "18px", "22px", "26px"];
+
+/// Wraps `body` the way XenForo quotes a message, so the quote links back to the post and its author.
+pub fn quote(author_name: &str, post_id: &str, author_id: &str, body: &str) -> String {
+    format!("[QUOTE=\"{author_name}, post: {post_id}, member: {author_id}\"]\n{}\n[/QUOTE]\n", body.trim())
+}
+
+/// Quote BBCode of a post built locally from its parsed content. Nested quotes are dropped like XenForo does.
+pub fn quote_post(post: &Post) -> String {
+    let body = post.contents.iter().filter(|c| !matches!(c, ContentType::QuoteBlock { .. })).map(block_bbcode).collect::<Vec<String>>().join("\n");
+    quote(&post.author_name, &post.post_id, &post.author_id, &body)
+}
+
+/// Quotes of several posts, one after another, for the reply editor.
+pub fn quote_posts(posts: &[Post]) -> String {
+    posts.iter().map(quote_post).collect::<Vec<String>>().join("\n")
+}
+
+/// BBCode XenForo would store for `contents`, the inverse of `parse_post_contents`.
+pub fn to_bbcode(contents: &[ContentType]) -> String {
+    contents.iter().map(block_bbcode).collect::<Vec<String>>().join("\n")
+}
+
+/// Converts rendered post html to BBCode. `html` can be the `bbWrapper` itself or any page fragment containing it.
+pub fn html_to_bbcode(html: &str) -> Result<String, VozError> {
+    let document = Document::from(html);
+    let node = document.find(Class("bbWrapper")).next().or_else(|| document.find(Name("body")).next()).or_parse(".bbWrapper", "Not found post content")?;
+    Ok(to_bbcode(&parse_post_contents(node)?))
+}
+
+fn block_bbcode(content: &ContentType) -> String {
+    match content {
+        ContentType::Html { content } => Document::from(content.as_str()).find(Name("body")).next().map(|n| n.text().trim().to_string()).unwrap_or_default(),
+        ContentType::Paragraph { content } => inline_bbcode(content),
+        ContentType::Heading { level, content } => format!("[HEADING={level}]{}[/HEADING]", inline_bbcode(content)),
+        ContentType::List { ordered, items } => {
+            let items = items.iter().map(|i| format!("[*]{}", to_bbcode(i))).collect::<Vec<String>>().join("\n");
+            format!("[LIST{}]\n{items}\n[/LIST]", if *ordered { "=1" } else { "" })
+        },
+        ContentType::HorizontalRule => "[HR][/HR]".to_string(),
+        ContentType::Image { src } => format!("[IMG]{src}[/IMG]"),
+        ContentType::QuoteBlock { author_id, author_name, post_id, content } => {
+            let parts = [author_name.clone(), post_id.as_ref().map(|id| format!("post: {id}")), author_id.as_ref().map(|id| format!("member: {id}"))];
+            let parts = parts.into_iter().flatten().collect::<Vec<String>>();
+            let attributes = if parts.is_empty() { String::new() } else { format!("=\"{}\"", parts.join(", ")) };
+            format!("[QUOTE{attributes}]\n{}\n[/QUOTE]", to_bbcode(content))
+        },
+        ContentType::CodeBlock { language, content } => {
+            let language = if language.is_empty() { String::new() } else { format!("={language}") };
+            format!("[CODE{language}]\n{content}\n[/CODE]")
+        },
+        ContentType::UrlBlock { url, .. } => format!("[URL unfurl=\"true\"]{url}[/URL]"),
+        ContentType::Spoiler { title, content } => {
+            let title = if title.is_empty() { String::new() } else { format!("=\"{title}\"") };
+            format!("[SPOILER{title}]\n{}\n[/SPOILER]", to_bbcode(content))
+        },
+        ContentType::Embeded { site, title, .. } => format!("[MEDIA={site}]{title}[/MEDIA]"),
+        ContentType::Table { header, rows } => {
+            let rows = header.iter().chain(rows.iter()).map(|r| format!("[TR]\n{}\n[/TR]", r.iter().map(cell_bbcode).collect::<Vec<String>>().join("\n"))).collect::<Vec<String>>().join("\n");
+            format!("[TABLE]\n{rows}\n[/TABLE]")
+        }
+    }
+}
+
+fn cell_bbcode(cell: &TableCell) -> String {
+    let tag = if cell.is_header { "TH" } else { "TD" };
+    format!("[{tag}]{}[/{tag}]", to_bbcode(&cell.content))
+}
+
+fn inline_bbcode(inlines: &[Inline]) -> String {
+    inlines.iter().map(|inline| match inline {
+        Inline::Text { text } => text.clone(),
+        Inline::Bold { content } => format!("[B]{}[/B]", inline_bbcode(content)),
+        Inline::Italic { content } => format!("[I]{}[/I]", inline_bbcode(content)),
+        Inline::Underline { content } => format!("[U]{}[/U]", inline_bbcode(content)),
+        Inline::Strike { content } => format!("[S]{}[/S]", inline_bbcode(content)),
+        Inline::Color { color, content } => format!("[COLOR={color}]{}[/COLOR]", inline_bbcode(content)),
+        Inline::Size { size, content } => {
+            let size = FONT_SIZES.iter().position(|s| s == size).map(|i| (i + 1).to_string()).unwrap_or(size.clone());
+            format!("[SIZE={size}]{}[/SIZE]", inline_bbcode(content))
+        },
+        Inline::Link { url, content } => match inline_bbcode(content) {
+            text if text == *url => format!("[URL]{url}[/URL]"),
+            text => format!("[URL='{url}']{text}[/URL]")
+        },
+        Inline::Mention { user_id, name } => format!("[USER={user_id}]@{name}[/USER]"),
+        Inline::Smilie { code, .. } => code.clone(),
+        Inline::InlineCode { code } => format!("[ICODE]{code}[/ICODE]"),
+        Inline::LineBreak => "\n".to_string()
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use select::{document::Document, predicate::{And, Class, Name}};
+
+    use super::*;
+    use crate::core::{bbcode_parser::parse_bbcode, parse_utils::parse_post};
+
+    #[test]
+    fn test_quote_post() {
+        let path = Path::new("resources/tests/thread.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+        let post = parse_post(document.find(And(Name("article"), Class("js-post"))).next().unwrap()).unwrap();
+
+        let result = quote_post(&post);
+        assert!(result.starts_with(&format!("[QUOTE=\"{}, post: {}, member: {}\"]\n", post.author_name, post.post_id, post.author_id)));
+        assert!(result.ends_with("\n[/QUOTE]\n"));
+        assert!(!result.contains("<"));
+        assert_eq!(quote("bboyviet", "1", "2", " hello "), "[QUOTE=\"bboyviet, post: 1, member: 2\"]\nhello\n[/QUOTE]\n");
+    }
+
+    #[test]
+    fn test_to_bbcode() {
+        let path = Path::new("resources/tests/post.html");
+        let content = fs::read_to_string(path).expect("File not found");
+
+        let result = html_to_bbcode(&content).unwrap();
+        let expected = concat!(
+            "[HEADING=1]Heading 1[/HEADING]\n[HEADING=2]Heading 2[/HEADING]\n[HEADING=3]Heading 3[/HEADING]\n",
+            "This is the body with an icon ;)\nLet's start to another element\n[HR][/HR]\n",
+            "[QUOTE]\n",
+            "SCOTLANDTurbine gió hình lục giác của Katrick Technologies có thể đặt trên nóc tòa nhà và sản xuất năng lượng ngay cả ở tốc độ gió thấp.\n",
+            "[IMG]https://i1-vnexpress.vnecdn.net/2023/12/25/VNE-Fan-3418-1703493351.jpg?w=680&h=0&q=100&dpr=1&fit=crop&s=El5Gcr43HPwoEZ_ArhDITQ[/IMG]\n",
+            "Thiết kế turbine Wind Panel của Katrick Technologies. Ảnh: [I]Katrick Technologies[/I]\n\n",
+            "Công ty khởi nghiệp Katrick Technologies ở Glasgow đang phát triển một thiết kế\n[/QUOTE]\n",
+            "[CODE=swift]\npublic func sampleFunction() -> String {\n        // Comment\n        let a = \"hello, world!\"\n        return a\n    }\n[/CODE]\n",
+            "[SPOILER=\"Spoiler\"]\nThis is spoiler content\n[/SPOILER]\n",
+            "[MEDIA=facebook]ChuyencuaHaNoi/posts/pfbid0GUskDC3GZPQjiVwWJXV7AFK5dW6LDwUDV44AviCYuc3quVuiYLqqeuMDPMgwP8oPl[/MEDIA]"
+        );
+        assert_eq!(result, expected);
+
+        let html = concat!(
+            r#"<div class="bbWrapper"><div class="bbMediaWrapper" data-media-site-id="youtube" data-media-key="dQw4w9WgXcQ"><div class="bbMediaWrapper-inner">"#,
+            r#"<iframe src="https://www.youtube.com/embed/dQw4w9WgXcQ?wmode=opaque&amp;start=0" width="560" height="315" frameborder="0" allowfullscreen="true"></iframe></div></div>"#,
+            r#"<div class="bbCodeBlock bbCodeBlock--unfurl js-unfurl fauxBlockLink" data-unfurl="true" data-result-id="418233" data-url="https://vnexpress.net/turbine-gio-luc-giac-4695338.html" data-host="vnexpress.net" data-pending="false">"#,
+            r#"<div class="contentRow"><div class="contentRow-main"><h3 class="contentRow-header js-unfurl-title"><a href="https://vnexpress.net/turbine-gio-luc-giac-4695338.html" class="link link--external fauxBlockLink-blockLink" target="_blank" rel="nofollow ugc noopener">Turbine gió lục giác</a></h3></div></div></div></div>"#
+        );
+        assert_eq!(html_to_bbcode(html).unwrap(), "[MEDIA=youtube]dQw4w9WgXcQ[/MEDIA]\n[URL unfurl=\"true\"]https://vnexpress.net/turbine-gio-luc-giac-4695338.html[/URL]");
+
+        let path = Path::new("resources/tests/inline.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let result = html_to_bbcode(&content).unwrap();
+        assert!(result.starts_with("[USER=1234567]@bboyviet[/USER] thím xem lại giúp cái [URL='https://voz.vn/t/hoi-ve-ban-phim-co.880001/']thread này[/URL] nhé :D\n"));
+        assert!(result.contains("[COLOR=#0000ff][SIZE=1][B]nhỏ xanh[/B][/SIZE][/COLOR]"));
+        assert!(result.contains("[SIZE=6]chữ to[/SIZE]"));
+        assert!(result.ends_with("[ICODE]cargo build --release[/ICODE] trước"));
+    }
+
+    #[test]
+    fn test_quote_attributes() {
+        let quote = |author_name: Option<&str>, post_id: Option<&str>, author_id: Option<&str>| to_bbcode(&[ContentType::QuoteBlock {
+            author_id: author_id.map(|s| s.to_string()),
+            author_name: author_name.map(|s| s.to_string()),
+            post_id: post_id.map(|s| s.to_string()),
+            content: Box::new(vec![ContentType::Paragraph { content: vec![Inline::Text { text: "trích".to_string() }] }])
+        }]);
+        assert_eq!(quote(Some("bboyviet"), Some("1"), Some("2")), "[QUOTE=\"bboyviet, post: 1, member: 2\"]\ntrích\n[/QUOTE]");
+        assert_eq!(quote(None, Some("1"), Some("2")), "[QUOTE=\"post: 1, member: 2\"]\ntrích\n[/QUOTE]");
+        assert_eq!(quote(None, None, Some("2")), "[QUOTE=\"member: 2\"]\ntrích\n[/QUOTE]");
+        assert_eq!(quote(None, None, None), "[QUOTE]\ntrích\n[/QUOTE]");
+
+        let result = parse_bbcode(&quote(None, Some("1"), Some("2")));
+        assert!(matches!(&result.contents[0], ContentType::QuoteBlock { author_id: Some(member), author_name: None, post_id: Some(post), .. } if member == "2" && post == "1"));
+    }
+}
@@ -0,0 +1,508 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_till1, take_while1},
+    character::complete::{char, space1},
+    combinator::{map, opt},
+    multi::many0,
+    sequence::{delimited, preceded, separated_pair, tuple},
+    IResult
+};
+use reqwest::Url;
+
+use super::{bbcode::FONT_SIZES, error::BbCodeError, models::{ContentType, Inline, TableCell}};
+
+/// Tags whose content is taken as is, without looking for nested tags.
+const RAW_TAGS: [&str; 5] = ["CODE", "ICODE", "PLAIN", "IMG", "MEDIA"];
+const INLINE_TAGS: [&str; 11] = ["B", "I", "U", "S", "COLOR", "SIZE", "FONT", "URL", "USER", "ISPOILER", "ICODE"];
+const BLOCK_TAGS: [&str; 17] = ["QUOTE", "CODE", "SPOILER", "IMG", "MEDIA", "LIST", "*", "HEADING", "HR", "TABLE", "TR", "TH", "TD", "LEFT", "CENTER", "RIGHT", "JUSTIFY"];
+
+/// Content tree of a BBCode message along with the malformed tags the parser skipped over.
+#[derive(Debug)]
+pub struct ParsedBbCode {
+    pub contents: Vec<ContentType>,
+    pub errors: Vec<BbCodeError>
+}
+
+/// Parses BBCode into the same tree `parse_post_contents` builds from rendered posts.
+/// Unknown tags are kept as text like XenForo does, unbalanced ones are closed where it makes sense and reported.
+pub fn parse_bbcode(input: &str) -> ParsedBbCode {
+    let mut parser = Parser { input, errors: vec![], stack: vec![Frame::root()] };
+    parser.run();
+    let Parser { mut stack, mut errors, .. } = parser;
+    let root = stack.pop().map(|f| f.children).unwrap_or_default();
+    let contents = blocks(root, input, &mut errors);
+    errors.sort_by_key(|e| e.position);
+    ParsedBbCode { contents, errors }
+}
+
+enum Token<'a> {
+    Open { name: &'a str, option: Option<&'a str>, attributes: Vec<(&'a str, &'a str)> },
+    Close { name: &'a str }
+}
+
+#[derive(Debug)]
+enum BbNode {
+    Text(String),
+    Tag { name: String, option: Option<String>, attributes: Vec<(String, String)>, children: Vec<BbNode>, position: usize }
+}
+
+struct Frame {
+    name: String,
+    option: Option<String>,
+    attributes: Vec<(String, String)>,
+    position: usize,
+    children: Vec<BbNode>
+}
+
+impl Frame {
+    fn root() -> Self {
+        Self { name: String::new(), option: None, attributes: vec![], position: 0, children: vec![] }
+    }
+
+    fn into_node(self) -> BbNode {
+        BbNode::Tag { name: self.name, option: self.option, attributes: self.attributes, children: self.children, position: self.position }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    errors: Vec<BbCodeError>,
+    stack: Vec<Frame>
+}
+
+impl<'a> Parser<'a> {
+    fn run(&mut self) {
+        let mut rest = self.input;
+        while !rest.is_empty() {
+            let position = self.input.len() - rest.len();
+            match token(rest) {
+                Ok((next, token)) => {
+                    let raw = &rest[..rest.len() - next.len()];
+                    rest = self.handle(token, raw, position, next);
+                },
+                Err(_) => {
+                    // A `[` that does not start a tag is plain text
+                    let first = rest.chars().next().map_or(1, |c| c.len_utf8());
+                    let end = rest[first..].find('[').map_or(rest.len(), |i| i + first);
+                    self.push_text(&rest[..end]);
+                    rest = &rest[end..];
+                }
+            }
+        }
+        while self.stack.len() > 1 {
+            self.close_top(true);
+        }
+    }
+
+    /// Applies one tag and returns the input left to parse.
+    fn handle(&mut self, token: Token<'_>, raw: &str, position: usize, rest: &'a str) -> &'a str {
+        match token {
+            Token::Open { name, option, attributes } => {
+                let name = name.to_ascii_uppercase();
+                if !INLINE_TAGS.contains(&name.as_str()) && !BLOCK_TAGS.contains(&name.as_str()) && !RAW_TAGS.contains(&name.as_str()) {
+                    self.push_text(raw);
+                    return rest;
+                }
+                let frame = Frame {
+                    name: name.clone(),
+                    option: option.map(|s| s.to_string()),
+                    attributes: attributes.into_iter().map(|(k, v)| (k.to_ascii_lowercase(), v.to_string())).collect(),
+                    position,
+                    children: vec![]
+                };
+                if RAW_TAGS.contains(&name.as_str()) {
+                    let closing = format!("[/{name}]");
+                    let (content, next) = match rest.to_ascii_uppercase().find(&closing) {
+                        Some(end) => (&rest[..end], &rest[end + closing.len()..]),
+                        None => {
+                            self.error(position, format!("[{name}] is never closed"));
+                            (rest, "")
+                        }
+                    };
+                    let mut frame = frame;
+                    frame.children.push(BbNode::Text(content.to_string()));
+                    self.push_node(frame.into_node());
+                    return next;
+                }
+                match name.as_str() {
+                    "HR" => self.push_node(frame.into_node()),
+                    "*" => {
+                        if self.top_name() == "*" {
+                            self.close_top(false);
+                        }
+                        if self.top_name() == "LIST" {
+                            self.stack.push(frame);
+                        } else {
+                            self.error(position, "[*] outside of a [LIST]");
+                            self.push_text(raw);
+                        }
+                    },
+                    _ => self.stack.push(frame)
+                }
+                rest
+            },
+            Token::Close { name } => {
+                let name = name.to_ascii_uppercase();
+                match self.stack.iter().rposition(|f| f.name == name) {
+                    Some(index) if index > 0 => {
+                        while self.stack.len() > index + 1 {
+                            self.close_top(true);
+                        }
+                        self.close_top(false);
+                    },
+                    // [HR] has no content, its closing tag is optional
+                    _ if name == "HR" => (),
+                    _ if INLINE_TAGS.contains(&name.as_str()) || BLOCK_TAGS.contains(&name.as_str()) => {
+                        self.error(position, format!("[/{name}] has no matching opening tag"));
+                        self.push_text(raw);
+                    },
+                    _ => self.push_text(raw)
+                }
+                rest
+            }
+        }
+    }
+
+    fn top_name(&self) -> &str {
+        self.stack.last().map(|f| f.name.as_str()).unwrap_or_default()
+    }
+
+    /// Closes the innermost tag, `implicit` when its closing tag is missing from the input.
+    fn close_top(&mut self, implicit: bool) {
+        let Some(frame) = self.stack.pop() else { return };
+        // List items are closed by the next [*] or by [/LIST]
+        if implicit && frame.name != "*" {
+            self.error(frame.position, format!("[{}] is never closed", frame.name));
+        }
+        self.push_node(frame.into_node());
+    }
+
+    fn push_node(&mut self, node: BbNode) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.children.push(node);
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some(frame) = self.stack.last_mut() {
+            match frame.children.last_mut() {
+                Some(BbNode::Text(last)) => last.push_str(text),
+                _ => frame.children.push(BbNode::Text(text.to_string()))
+            }
+        }
+    }
+
+    fn error<M: Into<String>>(&mut self, position: usize, message: M) {
+        self.errors.push(BbCodeError::new(self.input, position, message));
+    }
+}
+
+fn token(input: &str) -> IResult<&str, Token<'_>> {
+    alt((close_tag, open_tag))(input)
+}
+
+fn tag_name(input: &str) -> IResult<&str, &str> {
+    alt((tag("*"), take_while1(|c: char| c.is_ascii_alphanumeric())))(input)
+}
+
+fn quoted(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('"'), take_till(|c| c == '"' || c == '\n'), char('"')),
+        delimited(char('\''), take_till(|c| c == '\'' || c == '\n'), char('\''))
+    ))(input)
+}
+
+fn option_value(input: &str) -> IResult<&str, &str> {
+    alt((quoted, take_till1(|c| c == ']' || c == '[' || c == '\n')))(input)
+}
+
+fn attribute(input: &str) -> IResult<&str, (&str, &str)> {
+    preceded(space1, separated_pair(take_while1(|c: char| c.is_ascii_alphanumeric()), char('='), alt((quoted, take_till1(|c| c == ']' || c == ' ' || c == '\n')))))(input)
+}
+
+fn open_tag(input: &str) -> IResult<&str, Token<'_>> {
+    map(
+        delimited(char('['), tuple((tag_name, opt(preceded(char('='), option_value)), many0(attribute))), char(']')),
+        |(name, option, attributes)| Token::Open { name, option, attributes }
+    )(input)
+}
+
+fn close_tag(input: &str) -> IResult<&str, Token<'_>> {
+    map(delimited(tag("[/"), tag_name, char(']')), |name| Token::Close { name })(input)
+}
+
+fn blocks(nodes: Vec<BbNode>, input: &str, errors: &mut Vec<BbCodeError>) -> Vec<ContentType> {
+    let mut results: Vec<ContentType> = vec![];
+    let mut inlines: Vec<Inline> = vec![];
+    let nodes = nodes.into_iter().flat_map(|n| lift_blocks(n, input, errors)).collect::<Vec<BbNode>>();
+    for node in nodes {
+        match node {
+            BbNode::Tag { name, option, attributes, children, position } if is_block(&name, &attributes) => {
+                push_paragraph(&mut results, &mut inlines);
+                results.extend(block(&name, option, children, position, input, errors));
+            },
+            node => inlines.extend(inline(node, input, errors))
+        }
+    }
+    push_paragraph(&mut results, &mut inlines);
+    results
+}
+
+/// Splits inline tags around the blocks nested in them, `[B]a[QUOTE]b[/QUOTE]c[/B]` reads as
+/// `[B]a[/B][QUOTE]b[/QUOTE][B]c[/B]` since a paragraph cannot hold a block.
+fn lift_blocks(node: BbNode, input: &str, errors: &mut Vec<BbCodeError>) -> Vec<BbNode> {
+    let BbNode::Tag { name, option, attributes, children, position } = node else { return vec![node] };
+    if is_block(&name, &attributes) || RAW_TAGS.contains(&name.as_str()) {
+        return vec![BbNode::Tag { name, option, attributes, children, position }];
+    }
+    let mut result: Vec<BbNode> = vec![];
+    let mut part: Vec<BbNode> = vec![];
+    let children = children.into_iter().flat_map(|c| lift_blocks(c, input, errors)).collect::<Vec<BbNode>>();
+    for child in children {
+        match child {
+            BbNode::Tag { name: ref block, attributes: ref block_attributes, .. } if is_block(block, block_attributes) => {
+                if name == "URL" {
+                    errors.push(BbCodeError::new(input, position, format!("[{block}] inside [URL] is kept without its link")));
+                }
+                if !part.is_empty() {
+                    result.push(BbNode::Tag { name: name.clone(), option: option.clone(), attributes: attributes.clone(), children: std::mem::take(&mut part), position });
+                }
+                result.push(child);
+            },
+            child => part.push(child)
+        }
+    }
+    if !part.is_empty() || result.is_empty() {
+        result.push(BbNode::Tag { name, option, attributes, children: part, position });
+    }
+    result
+}
+
+fn is_block(name: &str, attributes: &[(String, String)]) -> bool {
+    BLOCK_TAGS.contains(&name) || (name == "URL" && attributes.iter().any(|(k, v)| k == "unfurl" && v == "true"))
+}
+
+/// Newlines around blocks belong to the block tags, so they are trimmed off the paragraph.
+fn push_paragraph(results: &mut Vec<ContentType>, inlines: &mut Vec<Inline>) {
+    let content = std::mem::take(inlines);
+    let start = content.iter().position(|i| *i != Inline::LineBreak).unwrap_or(content.len());
+    let end = content.iter().rposition(|i| *i != Inline::LineBreak).map_or(start, |i| i + 1);
+    let mut merged: Vec<Inline> = vec![];
+    for inline in content.into_iter().skip(start).take(end - start) {
+        match (merged.last_mut(), inline) {
+            (Some(Inline::Text { text: last }), Inline::Text { text }) => last.push_str(&text),
+            (_, inline) => merged.push(inline)
+        }
+    }
+    let content = merged;
+    if content.iter().any(|i| !matches!(i, Inline::Text { text } if text.trim().is_empty())) {
+        results.push(ContentType::Paragraph { content });
+    }
+}
+
+fn block(name: &str, option: Option<String>, children: Vec<BbNode>, position: usize, input: &str, errors: &mut Vec<BbCodeError>) -> Vec<ContentType> {
+    let result = match name {
+        "QUOTE" => {
+            let (mut author_name, mut post_id, mut author_id) = (None, None, None);
+            for part in option.unwrap_or_default().split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                match part.split_once(':').map(|(k, v)| (k.trim(), v.trim().to_string())) {
+                    Some(("post", id)) => post_id = Some(id),
+                    Some(("member", id)) => author_id = Some(id),
+                    _ => author_name = Some(part.to_string())
+                }
+            }
+            ContentType::QuoteBlock { author_id, author_name, post_id, content: Box::new(blocks(children, input, errors)) }
+        },
+        "CODE" => ContentType::CodeBlock { language: option.unwrap_or_default().to_lowercase(), content: raw_text(&children).trim_matches(|c| c == '\n' || c == '\r').to_string() },
+        "SPOILER" => ContentType::Spoiler { title: option.unwrap_or_default(), content: Box::new(blocks(children, input, errors)) },
+        "IMG" => ContentType::Image { src: raw_text(&children).trim().to_string() },
+        "MEDIA" => ContentType::Embeded { site: option.unwrap_or_default().to_lowercase(), title: raw_text(&children).trim().to_string(), link: None },
+        "URL" => {
+            let url = raw_text(&children).trim().to_string();
+            let host = Url::parse(&url).ok().and_then(|u| u.host_str().map(|h| h.to_string())).unwrap_or_default();
+            ContentType::UrlBlock { thumbnail: None, title: url.clone(), content: String::new(), host, url }
+        },
+        "LIST" => {
+            let ordered = option.is_some_and(|o| !o.eq_ignore_ascii_case("disc") && !o.eq_ignore_ascii_case("circle") && !o.eq_ignore_ascii_case("square"));
+            let items = children.into_iter().filter_map(|n| match n {
+                BbNode::Tag { name, children, .. } if name == "*" => Some(blocks(children, input, errors)),
+                _ => None
+            }).collect();
+            ContentType::List { ordered, items }
+        },
+        "HEADING" => match option.as_deref().and_then(|o| o.trim().parse::<i64>().ok()).filter(|l| (1..=3).contains(l)) {
+            Some(level) => ContentType::Heading { level, content: inlines(children, input, errors) },
+            None => {
+                errors.push(BbCodeError::new(input, position, "[HEADING] needs a level from 1 to 3"));
+                return blocks(children, input, errors);
+            }
+        },
+        "HR" => ContentType::HorizontalRule,
+        "TABLE" => {
+            let mut rows = children.into_iter().filter_map(|n| match n {
+                BbNode::Tag { name, children, .. } if name == "TR" => Some(children.into_iter().filter_map(|c| match c {
+                    BbNode::Tag { name, children, .. } if name == "TH" || name == "TD" => Some(TableCell { content: blocks(children, input, errors), colspan: 1, rowspan: 1, is_header: name == "TH" }),
+                    _ => None
+                }).collect::<Vec<TableCell>>()),
+                _ => None
+            }).filter(|r| !r.is_empty()).collect::<Vec<Vec<TableCell>>>();
+            let header = match rows.first() {
+                Some(first) if first.iter().all(|c| c.is_header) => Some(rows.remove(0)),
+                _ => None
+            };
+            ContentType::Table { header, rows }
+        },
+        // Alignment and stray table or list parts only wrap other content
+        _ => return blocks(children, input, errors)
+    };
+    vec![result]
+}
+
+fn inlines(nodes: Vec<BbNode>, input: &str, errors: &mut Vec<BbCodeError>) -> Vec<Inline> {
+    nodes.into_iter().flat_map(|n| inline(n, input, errors)).collect()
+}
+
+fn inline(node: BbNode, input: &str, errors: &mut Vec<BbCodeError>) -> Vec<Inline> {
+    let (name, option, children, position) = match node {
+        BbNode::Text(text) => {
+            let mut result = vec![];
+            for (index, line) in text.split('\n').enumerate() {
+                if index > 0 {
+                    result.push(Inline::LineBreak);
+                }
+                let line = line.trim_end_matches('\r');
+                if !line.is_empty() {
+                    result.push(Inline::Text { text: line.to_string() });
+                }
+            }
+            return result;
+        },
+        BbNode::Tag { name, option, children, position, .. } => (name, option, children, position)
+    };
+    let result = match name.as_str() {
+        "B" => Inline::Bold { content: inlines(children, input, errors) },
+        "I" => Inline::Italic { content: inlines(children, input, errors) },
+        "U" => Inline::Underline { content: inlines(children, input, errors) },
+        "S" => Inline::Strike { content: inlines(children, input, errors) },
+        "COLOR" if option.is_some() => Inline::Color { color: option.unwrap_or_default(), content: inlines(children, input, errors) },
+        "SIZE" => {
+            let size = option.unwrap_or_default();
+            let size = match size.trim().parse::<usize>() {
+                Ok(index) if (1..=FONT_SIZES.len()).contains(&index) => FONT_SIZES[index - 1].to_string(),
+                _ if size.ends_with("px") && size[..size.len() - 2].parse::<u32>().is_ok() => size,
+                _ => {
+                    errors.push(BbCodeError::new(input, position, format!("Invalid [SIZE] value \"{size}\"")));
+                    return inlines(children, input, errors);
+                }
+            };
+            Inline::Size { size, content: inlines(children, input, errors) }
+        },
+        "URL" => {
+            let content = inlines(children, input, errors);
+            let url = option.unwrap_or_else(|| plain_text(&content)).trim().to_string();
+            Inline::Link { url, content }
+        },
+        "USER" if option.is_some() => {
+            let name = plain_text(&inlines(children, input, errors)).trim().trim_start_matches('@').to_string();
+            Inline::Mention { user_id: option.unwrap_or_default().trim().to_string(), name }
+        },
+        "ICODE" => Inline::InlineCode { code: raw_text(&children) },
+        "PLAIN" => Inline::Text { text: raw_text(&children) },
+        _ if BLOCK_TAGS.contains(&name.as_str()) => {
+            errors.push(BbCodeError::new(input, position, format!("[{name}] cannot be used here, only its text is kept")));
+            return inlines(children, input, errors);
+        },
+        // Fonts and inline spoilers keep only their text
+        _ => return inlines(children, input, errors)
+    };
+    vec![result]
+}
+
+fn raw_text(nodes: &[BbNode]) -> String {
+    nodes.iter().map(|n| match n {
+        BbNode::Text(text) => text.clone(),
+        BbNode::Tag { children, .. } => raw_text(children)
+    }).collect()
+}
+
+fn plain_text(inlines: &[Inline]) -> String {
+    inlines.iter().map(|i| match i {
+        Inline::Text { text } => text.clone(),
+        Inline::Bold { content } | Inline::Italic { content } | Inline::Underline { content } | Inline::Strike { content } => plain_text(content),
+        Inline::Color { content, .. } | Inline::Size { content, .. } | Inline::Link { content, .. } => plain_text(content),
+        Inline::Mention { name, .. } => format!("@{name}"),
+        Inline::Smilie { code, .. } => code.clone(),
+        Inline::InlineCode { code } => code.clone(),
+        Inline::LineBreak => "\n".to_string()
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::core::bbcode::{html_to_bbcode, to_bbcode};
+
+    #[test]
+    fn test_round_trip() {
+        for fixture in ["resources/tests/post.html", "resources/tests/inline.html", "resources/tests/table.html"] {
+            let content = fs::read_to_string(Path::new(fixture)).expect("File not found");
+            let bbcode = html_to_bbcode(&content).unwrap();
+            let result = parse_bbcode(&bbcode);
+            assert!(result.errors.is_empty(), "{fixture}: {:?}", result.errors);
+            assert_eq!(to_bbcode(&result.contents), bbcode, "{fixture}");
+        }
+    }
+
+    #[test]
+    fn test_parse_bbcode() {
+        let result = parse_bbcode("[quote=\"bboyviet, post: 1, member: 2\"]\n[b]Hi[/b] [USER=2]@bboyviet[/USER]\n[/QUOTE]\n[LIST=1]\n[*]one\n[*]two\n[/LIST]\n[size=6]to[/size] [url=https://voz.vn]voz[/url]");
+        assert!(result.errors.is_empty());
+        assert_eq!(result.contents.len(), 3);
+
+        let ContentType::QuoteBlock { author_id, author_name, post_id, content } = &result.contents[0] else { panic!("Expected quote, got {:?}", result.contents[0]) };
+        assert_eq!((author_name.as_deref(), post_id.as_deref(), author_id.as_deref()), (Some("bboyviet"), Some("1"), Some("2")));
+        assert!(matches!(&content[0], ContentType::Paragraph { content } if content[2] == Inline::Mention { user_id: "2".to_string(), name: "bboyviet".to_string() }));
+        assert!(matches!(&result.contents[1], ContentType::List { ordered: true, items } if items.len() == 2));
+        let ContentType::Paragraph { content } = &result.contents[2] else { panic!("Expected paragraph, got {:?}", result.contents[2]) };
+        assert_eq!(content[0], Inline::Size { size: "22px".to_string(), content: vec![Inline::Text { text: "to".to_string() }] });
+        assert_eq!(content[2], Inline::Link { url: "https://voz.vn".to_string(), content: vec![Inline::Text { text: "voz".to_string() }] });
+    }
+
+    #[test]
+    fn test_malformed() {
+        let result = parse_bbcode("[B]bold [I]both[/B] text[/U]\n[CODE]let a = [b];\n[SIZE=huge]x[/SIZE] [unknown]tag[/unknown] [not a tag");
+        let messages = result.errors.iter().map(|e| (e.line, e.column, e.message.as_str())).collect::<Vec<(usize, usize, &str)>>();
+        assert_eq!(messages, vec![
+            (1, 9, "[I] is never closed"),
+            (1, 25, "[/U] has no matching opening tag"),
+            (2, 1, "[CODE] is never closed")
+        ]);
+        let ContentType::Paragraph { content } = &result.contents[0] else { panic!("Expected paragraph, got {:?}", result.contents[0]) };
+        assert!(matches!(&content[0], Inline::Bold { content } if matches!(content[1], Inline::Italic { .. })));
+        assert_eq!(content[1], Inline::Text { text: " text[/U]".to_string() });
+        assert!(matches!(&result.contents[1], ContentType::CodeBlock { content, .. } if content.ends_with("[not a tag")));
+
+        let result = parse_bbcode("[SIZE=huge]x[/SIZE] [unknown]tag[/unknown] [not a tag [*] [HR]");
+        assert_eq!(result.errors.iter().map(|e| e.position).collect::<Vec<usize>>(), vec![0, 54]);
+        let ContentType::Paragraph { content } = &result.contents[0] else { panic!("Expected paragraph, got {:?}", result.contents[0]) };
+        assert_eq!(content, &vec![Inline::Text { text: "x [unknown]tag[/unknown] [not a tag [*] ".to_string() }]);
+        assert!(matches!(result.contents[1], ContentType::HorizontalRule));
+    }
+
+    #[test]
+    fn test_block_in_inline() {
+        let result = parse_bbcode("[URL=https://voz.vn][IMG]https://i.imgur.com/abc.png[/IMG][/URL]\n[B]trước [QUOTE=\"bboyviet\"]trích[/QUOTE] sau[/B]\n[HEADING=1]a [SPOILER]b[/SPOILER][/HEADING]");
+        assert_eq!(result.errors.iter().map(|e| (e.position, e.message.as_str())).collect::<Vec<(usize, &str)>>(), vec![
+            (0, "[IMG] inside [URL] is kept without its link"),
+            (131, "[SPOILER] cannot be used here, only its text is kept")
+        ]);
+        assert!(matches!(&result.contents[0], ContentType::Image { src } if src == "https://i.imgur.com/abc.png"));
+        let bold = |text: &str| vec![Inline::Bold { content: vec![Inline::Text { text: text.to_string() }] }];
+        assert!(matches!(&result.contents[1], ContentType::Paragraph { content } if content == &bold("trước ")));
+        assert!(matches!(&result.contents[2], ContentType::QuoteBlock { author_name: Some(name), content, .. } if name == "bboyviet" && content.len() == 1));
+        assert!(matches!(&result.contents[3], ContentType::Paragraph { content } if content == &bold(" sau")));
+        assert!(matches!(&result.contents[4], ContentType::Heading { content, .. } if content == &vec![Inline::Text { text: "a ".to_string() }, Inline::Text { text: "b".to_string() }]));
+    }
+}
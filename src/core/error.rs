@@ -0,0 +1,125 @@
+use std::fmt::{self, Display};
+
+/// Every failure surfaced by `VozCore` and the `parse_*` helpers.
+#[derive(Debug)]
+pub enum VozError {
+    /// The request never produced a response (DNS, TLS, connection reset, body read...)
+    Transport(reqwest_middleware::Error),
+    /// The server answered with a non-success status and no XenForo error message
+    HttpStatus { status: u16 },
+    /// The action requires a logged in user, or the session has expired
+    NotLoggedIn,
+    /// The user is logged in but is not allowed to view the page or perform the action
+    PermissionDenied { message: String },
+    /// Login rejected the username/password or the two-step verification code
+    InvalidCredentials { message: String },
+    /// The page layout does not match what the parser expects
+    Parse { selector: String, message: String },
+    /// XenForo rendered an error message for the request
+    XenForo { message: String },
+    /// Flood control or HTTP 429, `retry_after` is in seconds when known
+    RateLimited { message: String, retry_after: Option<u64> },
+    /// The window for editing or deleting the content has passed
+    TimeLimitExpired { message: String },
+    /// An argument was rejected before sending the request
+    InvalidArgument { message: String }
+}
+
+impl VozError {
+    pub fn parse<S: Into<String>, M: Into<String>>(selector: S, message: M) -> Self {
+        Self::Parse { selector: selector.into(), message: message.into() }
+    }
+
+    /// Machine-readable identifier of the error kind, stable across releases.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Transport(_) => "transport",
+            Self::HttpStatus { .. } => "http_status",
+            Self::NotLoggedIn => "not_logged_in",
+            Self::PermissionDenied { .. } => "permission_denied",
+            Self::InvalidCredentials { .. } => "invalid_credentials",
+            Self::Parse { .. } => "parse",
+            Self::XenForo { .. } => "xenforo",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::TimeLimitExpired { .. } => "time_limit_expired",
+            Self::InvalidArgument { .. } => "invalid_argument"
+        }
+    }
+}
+
+impl Display for VozError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "Network error: {e}"),
+            Self::HttpStatus { status } => write!(f, "Unexpected HTTP status {status}"),
+            Self::NotLoggedIn => write!(f, "Not login yet"),
+            Self::PermissionDenied { message } => write!(f, "{message}"),
+            Self::InvalidCredentials { message } => write!(f, "{message}"),
+            Self::Parse { selector, message } => write!(f, "{message} ({selector})"),
+            Self::XenForo { message } => write!(f, "{message}"),
+            Self::RateLimited { message, .. } => write!(f, "{message}"),
+            Self::TimeLimitExpired { message } => write!(f, "{message}"),
+            Self::InvalidArgument { message } => write!(f, "Invalid argument: {message}")
+        }
+    }
+}
+
+impl std::error::Error for VozError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<reqwest_middleware::Error> for VozError {
+    fn from(value: reqwest_middleware::Error) -> Self {
+        Self::Transport(value)
+    }
+}
+
+impl From<reqwest::Error> for VozError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Transport(reqwest_middleware::Error::Reqwest(value))
+    }
+}
+
+/// Turns a missing node/attribute into a `VozError::Parse` carrying the selector that failed.
+pub trait OrParseError<T> {
+    fn or_parse(self, selector: &str, message: &str) -> Result<T, VozError>;
+}
+
+impl<T> OrParseError<T> for Option<T> {
+    fn or_parse(self, selector: &str, message: &str) -> Result<T, VozError> {
+        self.ok_or_else(|| VozError::parse(selector, message))
+    }
+}
+
+/// A malformed piece of BBCode. The parser recovers from it, so these are reported next to the parsed content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BbCodeError {
+    /// Byte offset in the input
+    pub position: usize,
+    /// 1-based line and column (in characters) of `position`
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+impl BbCodeError {
+    pub fn new<M: Into<String>>(input: &str, position: usize, message: M) -> Self {
+        let before = &input[..position];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        Self { position, line, column, message: message.into() }
+    }
+}
+
+impl Display for BbCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
+
+impl std::error::Error for BbCodeError {}
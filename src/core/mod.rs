@@ -2,4 +2,9 @@ pub mod parse_utils;
 pub mod session;
 pub mod voz_core;
 pub mod models;
+pub mod error;
+pub mod storage;
+pub mod bbcode;
+pub mod bbcode_parser;
+pub mod renderer;
 mod post_parse_utils;
\ No newline at end of file
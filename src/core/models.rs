@@ -1,10 +1,12 @@
+use std::{collections::HashMap, sync::atomic::{AtomicU64, Ordering}, time::{SystemTime, UNIX_EPOCH}};
+
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "status", rename_all = "camelCase")]
 pub enum VozResponse<T: Serialize> {
     Success { data: T },
-    Failed { message: String }
+    Failed { code: String, message: String }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -33,6 +35,34 @@ pub struct Forum {
     pub threads: Vec<ThreadItem>
 }
 
+/// The thread lists linked from the account menu.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FindThreadsKind {
+    Started,
+    Contributed,
+    Unanswered
+}
+
+impl FindThreadsKind {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Contributed => "contributed",
+            Self::Unanswered => "unanswered"
+        }
+    }
+}
+
+/// A paginated list of threads outside of a forum, e.g. watched threads or find-threads results.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadList {
+    pub threads: Vec<ThreadItem>,
+    pub current_page: String,
+    pub total_page: String
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ThreadItem {
@@ -43,7 +73,8 @@ pub struct ThreadItem {
     pub is_read: bool,
     pub replies: String,
     pub latest: String,
-    pub author: String
+    pub author: String,
+    pub forum_name: Option<String>
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -61,24 +92,107 @@ pub struct User {
     pub avatar: String
 }
 
+/// The logged in user with the unread badges shown in the navigation bar.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentUser {
+    #[serde(flatten)]
+    pub user: User,
+    pub unread_alerts: i64,
+    pub unread_conversations: i64
+}
+
+/// A custom user field from the About tab, `id` is XenForo's field id.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileField {
+    pub id: String,
+    pub title: String,
+    pub value: String
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberProfile {
+    #[serde(flatten)]
+    pub user: User,
+    pub title: Option<String>,
+    pub joined: Option<String>,
+    pub last_seen: Option<String>,
+    pub message_count: String,
+    pub reaction_score: String,
+    pub location: Option<String>,
+    pub banner: Option<String>,
+    pub about: Option<String>,
+    pub custom_fields: Vec<ProfileField>,
+    /// `None` when the visitor cannot follow this member, e.g. on their own profile
+    pub is_following: Option<bool>,
+    pub recent_activity: Vec<ActivityItem>,
+    pub profile_posts: Vec<ActivityItem>
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag="type", rename_all = "camelCase")]
 pub enum LoginResult {
     MFA { url: String },
-    Success { user: String, session: String, tfa_trust: Option<String>, info: User }
+    Success { user: String, session: String, tfa_trust: Option<String>, info: CurrentUser }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Thread {
+    pub id: String,
     pub prefix:  Option<ThreadPrefix>,
     pub title: String,
+    pub starter: Option<User>,
+    pub breadcrumbs: Vec<Breadcrumb>,
+    pub is_watched: bool,
+    /// Bookmark state of the first post, unknown when it is not on the loaded page
+    pub is_bookmarked: Option<bool>,
     pub current_page: String,
     pub total_page: String,
     pub can_reply: bool,
     pub posts: Vec<Post>,
     pub posts_html: String,
-    pub reactions: Vec<Reaction>
+    pub reactions: Vec<Reaction>,
+    pub poll: Option<Poll>
+}
+
+/// A forum or category the thread lives under, from the outermost one down.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    pub id: String,
+    pub title: String,
+    /// `f` for forums, `c` for categories
+    pub forum_type: String,
+    pub link: String
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PollOption {
+    /// Missing when neither the vote form nor the voter links are shown
+    pub id: Option<String>,
+    pub text: String,
+    pub votes: Option<i64>,
+    pub percentage: Option<f64>,
+    pub is_voted: bool
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Poll {
+    pub question: String,
+    pub options: Vec<PollOption>,
+    /// How many options can be picked, 0 when there is no limit
+    pub max_votes: i64,
+    pub total_voters: Option<i64>,
+    pub close_date: Option<String>,
+    pub close_timestamp: Option<i64>,
+    pub is_closed: bool,
+    pub has_voted: bool,
+    pub can_vote: bool
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -89,9 +203,12 @@ pub struct Post {
     pub author_id: String,
     pub author_name: String,
     pub author_avatar: String,
+    /// Profile of the author, pass `author_id` to `VozCore::get_member` to load it.
+    pub author_link: Option<String>,
     pub created: String,
     pub last_edited: Option<String>,
     pub html_content: String,
+    pub contents: Vec<ContentType>,
     pub warning_message: Option<String>,
     pub position: i64,
     pub can_edit: bool,
@@ -101,7 +218,224 @@ pub struct Post {
     pub can_react: bool,
     pub is_reacted_to: bool,
     pub visitor_reaction_id: Option<i64>,
-    pub reactions: Option<ReactionSummary>
+    pub reactions: Option<ReactionSummary>,
+    pub is_bookmarked: bool,
+    pub is_ignored: bool
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub content_type: String,
+    pub content_id: String,
+    pub title: String,
+    pub snippet: String,
+    pub author: User,
+    pub link: String,
+    pub labels: Vec<String>,
+    pub note: Option<String>,
+    pub time: String,
+    pub timestamp: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkList {
+    pub bookmarks: Vec<Bookmark>,
+    pub current_page: String,
+    pub total_page: String
+}
+
+/// What is left of a post after `delete_post`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedPost {
+    pub post_id: String,
+    pub hard_deleted: bool,
+    pub reason: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationItem {
+    pub id: String,
+    pub title: String,
+    pub starter: String,
+    pub participants: Vec<String>,
+    pub replies: String,
+    pub latest: String,
+    pub latest_author: Option<String>,
+    pub is_unread: bool
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationList {
+    pub conversations: Vec<ConversationItem>,
+    pub current_page: String,
+    pub total_page: String
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub title: String,
+    pub current_page: String,
+    pub total_page: String,
+    pub can_reply: bool,
+    pub participants: Vec<User>,
+    pub messages: Vec<Post>
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertKind {
+    Quote,
+    Mention,
+    Reaction,
+    Reply,
+    Follow,
+    Other
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub id: String,
+    pub actor: Option<User>,
+    pub kind: AlertKind,
+    pub message: String,
+    pub link: Option<String>,
+    pub is_read: bool,
+    pub time: String,
+    pub timestamp: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertList {
+    pub alerts: Vec<Alert>,
+    pub current_page: String,
+    pub total_page: String
+}
+
+/// Sort order of search results, `Replies` only applies when results are grouped by thread.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchOrder {
+    #[default]
+    Relevance,
+    Date,
+    Replies
+}
+
+impl SearchOrder {
+    pub fn value(&self) -> &'static str {
+        match self {
+            Self::Relevance => "relevance",
+            Self::Date => "date",
+            Self::Replies => "replies"
+        }
+    }
+}
+
+/// Constraints of the advanced search form. Dates use the `YYYY-MM-DD` format.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SearchOptions {
+    pub title_only: bool,
+    pub members: Vec<String>,
+    pub forum_ids: Vec<String>,
+    pub include_sub_forums: bool,
+    pub newer_than: Option<String>,
+    pub older_than: Option<String>,
+    pub order: SearchOrder,
+    pub group_by_thread: bool
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchHitKind {
+    Thread,
+    Post
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub id: String,
+    pub title: String,
+    pub snippet: String,
+    pub author: User,
+    pub forum_id: Option<String>,
+    pub forum_name: Option<String>,
+    pub replies: Option<String>,
+    pub link: String,
+    pub time: String,
+    pub timestamp: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub search_id: Option<String>,
+    pub query: String,
+    pub options: SearchOptions,
+    pub hits: Vec<SearchHit>,
+    pub current_page: String,
+    pub total_page: String
+}
+
+/// The feeds listed under What's New.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WhatsNewKind {
+    Posts,
+    ProfilePosts,
+    LatestActivity,
+    NewsFeed
+}
+
+impl WhatsNewKind {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::Posts => "posts",
+            Self::ProfilePosts => "profile-posts",
+            Self::LatestActivity => "latest-activity",
+            Self::NewsFeed => "news-feed"
+        }
+    }
+}
+
+/// A news feed entry such as "`actor` replied to the thread `target`", profile posts use the profile owner as target.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityItem {
+    pub actor: Option<User>,
+    pub action: String,
+    pub target: Option<String>,
+    pub link: Option<String>,
+    pub snippet: String,
+    pub time: String,
+    pub timestamp: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityList {
+    pub activities: Vec<ActivityItem>,
+    pub current_page: i64,
+    pub has_more: bool,
+    /// Cursor of the "Show older items" link, activity feeds are not paged.
+    pub before_id: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum WhatsNew {
+    Threads(ThreadList),
+    Activities(ActivityList)
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -119,17 +453,69 @@ pub struct Reaction {
     pub title: String
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactedUser {
+    pub user: User,
+    pub reaction: Reaction,
+    pub time: String,
+    pub timestamp: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PostReactions {
+    pub users: Vec<ReactedUser>,
+    pub current_page: i64,
+    pub has_more: bool
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum ContentType {
+    /// Markup the parser has no structured form for yet
     Html { content: String },
+    /// A run of text and inline elements between two blocks
+    Paragraph { content: Vec<Inline> },
+    Heading { level: i64, content: Vec<Inline> },
+    List { ordered: bool, items: Vec<Vec<ContentType>> },
+    HorizontalRule,
     Image { src: String },
     QuoteBlock { author_id: Option<String>, author_name: Option<String>, post_id: Option<String>, content: Box<Vec<ContentType>> },
     CodeBlock { language: String, content: String },
     UrlBlock { thumbnail: Option<String>, title: String, content: String, host: String, url: String },
     Spoiler { title: String, content: Box<Vec<ContentType>> },
-    Embeded { site: String, title: String, link: String },
-    Table { content: String }
+    Embeded { site: String, title: String, link: Option<String> },
+    Table { header: Option<Vec<TableCell>>, rows: Vec<Vec<TableCell>> }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TableCell {
+    pub content: Vec<ContentType>,
+    pub colspan: i64,
+    pub rowspan: i64,
+    pub is_header: bool
+}
+
+/// Text level content of a paragraph. Formatting variants wrap the inlines they apply to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Inline {
+    Text { text: String },
+    Bold { content: Vec<Inline> },
+    Italic { content: Vec<Inline> },
+    Underline { content: Vec<Inline> },
+    Strike { content: Vec<Inline> },
+    /// Css color as written in the html, e.g. `rgb(184, 49, 47)` or `#ff0000`
+    Color { color: String, content: Vec<Inline> },
+    /// Css font size, e.g. `15px`
+    Size { size: String, content: Vec<Inline> },
+    Link { url: String, content: Vec<Inline> },
+    Mention { user_id: String, name: String },
+    Smilie { code: String, src: String },
+    InlineCode { code: String },
+    LineBreak
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -138,3 +524,123 @@ pub struct LoginInfo {
     pub url: String,
     pub token: String
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorForm {
+    pub url: String,
+    pub token: String,
+    pub attachment_hash: Option<String>,
+    pub hidden_fields: HashMap<String, String>
+}
+
+/// Message body sent to the editor, either as rich text html or raw BBCode.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", content = "content", rename_all = "camelCase")]
+pub enum MessageContent {
+    Html(String),
+    BBCode(String)
+}
+
+impl MessageContent {
+    pub fn field(&self) -> (&'static str, String) {
+        match self {
+            Self::Html(s) => ("message_html", s.clone()),
+            Self::BBCode(s) => ("message", s.clone())
+        }
+    }
+}
+
+/// Settings of the post thread form besides title, prefix and message.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ThreadOptions {
+    pub watch: bool,
+    pub watch_email: bool,
+    pub attachment_hash: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewThread {
+    pub thread_id: String,
+    pub post: Post
+}
+
+/// Where uploads go. `hash` groups the uploads of one message, pass it as the attachment hash of
+/// `reply`, `reply_conversation` or `ThreadOptions` to attach them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentContext {
+    pub content_type: String,
+    pub context: HashMap<String, String>,
+    pub hash: String
+}
+
+impl AttachmentContext {
+    pub fn reply(thread_id: String) -> Self {
+        Self::new("post", "thread_id", thread_id)
+    }
+
+    pub fn new_thread(forum_id: String) -> Self {
+        Self::new("post", "node_id", forum_id)
+    }
+
+    pub fn conversation_reply(conversation_id: String) -> Self {
+        Self::new("conversation_message", "conversation_id", conversation_id)
+    }
+
+    /// XenForo accepts any client chosen hash, it only has to be unique to the message being written.
+    fn new(content_type: &str, key: &str, id: String) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+        let hash = format!("{:032x}", nanos.wrapping_add(COUNTER.fetch_add(1, Ordering::Relaxed) as u128));
+        Self { content_type: content_type.to_string(), context: HashMap::from([(key.to_string(), id)]), hash }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub attachment_id: String,
+    pub filename: String,
+    pub thumbnail_url: Option<String>,
+    pub link: String
+}
+
+impl Attachment {
+    /// Inserts the attachment inline instead of listing it under the message.
+    pub fn bbcode(&self) -> String {
+        format!("[ATTACH]{}[/ATTACH]", self.attachment_id)
+    }
+}
+
+/// Body returned by XenForo when a request is sent with `_xfResponseType=json`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XfJsonResponse {
+    pub status: Option<String>,
+    pub errors: Option<serde_json::Value>,
+    pub message: Option<String>,
+    pub redirect: Option<String>,
+    pub html: Option<XfJsonHtml>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct XfJsonHtml {
+    pub content: Option<String>
+}
+
+impl XfJsonResponse {
+    /// Error messages, whether XenForo sent them as a list or keyed by form field.
+    pub fn error_messages(&self) -> Vec<String> {
+        match &self.errors {
+            Some(serde_json::Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect(),
+            Some(serde_json::Value::Object(items)) => items.values().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect(),
+            Some(serde_json::Value::String(s)) => vec![s.clone()],
+            _ => vec![]
+        }
+    }
+}
@@ -1,10 +1,11 @@
-use std::{error::Error, collections::HashMap};
+use std::collections::HashMap;
 
-use select::{predicate::*, node::Node};
+use select::{predicate::*, node::Node, document::Document};
 
 use crate::core::models::*;
 
-use super::post_parse_utils::{parse_content, parse_reactions, parse_list_reactions};
+use super::error::{VozError, OrParseError};
+use super::post_parse_utils::{parse_content, parse_block, parse_inline, normalize_inlines, parse_reactions, parse_list_reactions};
 
 pub trait TrimmedString {
     fn trimmed(&self) -> String;
@@ -16,8 +17,8 @@ impl TrimmedString for String {
     }
 }
 
-pub fn parse_catagories(node: Node) -> Result<Category, Box<dyn Error>> {
-    let title = node.find(Class("block-header").descendant(Name("a"))).next().ok_or("title element not found")?.text();
+pub fn parse_catagories(node: Node) -> Result<Category, VozError> {
+    let title = node.find(Class("block-header").descendant(Name("a"))).next().or_parse(".block-header a", "title element not found")?.text();
     let mut errors = vec![];
     let forums = node.find(Class("node"))
                                     .filter_map(|child| parse_forum_item(child).map_err(|e| errors.push(e)).ok())
@@ -29,16 +30,16 @@ pub fn parse_catagories(node: Node) -> Result<Category, Box<dyn Error>> {
     }
 }
 
-pub fn parse_forum_item(node: Node) -> Result<ForumItem, Box<dyn Error>> {
-    let title = node.find(Name("h3")).next().ok_or("title element not found")?.text();
+pub fn parse_forum_item(node: Node) -> Result<ForumItem, VozError> {
+    let title = node.find(Name("h3")).next().or_parse("h3", "title element not found")?.text();
     let classes = node.attr("class").unwrap_or("");
     let mut meta = node.find(Class("pairs--rows").descendant(Name("dd")));
-    let thread_number = meta.next().ok_or("Cannot find thread number node")?.text();
-    let message_number = meta.next().ok_or("Cannot find message number node")?.text();
+    let thread_number = meta.next().or_parse(".pairs--rows dd", "Cannot find thread number node")?.text();
+    let message_number = meta.next().or_parse(".pairs--rows dd", "Cannot find message number node")?.text();
     let is_read = classes.contains("node--read");
     let forum_type = if classes.contains("node--forum") { "f" } else { "s" };
-    let id_str = classes.split(' ').find(|x| x.starts_with("node--id")).ok_or("Cannot find forum id class")?;
-    let id = id_str.split("node--id").last().ok_or("Cannot find forum id")?;
+    let id_str = classes.split(' ').find(|x| x.starts_with("node--id")).or_parse(".node--id", "Cannot find forum id class")?;
+    let id = id_str.split("node--id").last().or_parse(".node--id", "Cannot find forum id")?;
     Ok(ForumItem {
         id: id.to_string(),
         title: title.trim().to_string(),
@@ -49,28 +50,36 @@ pub fn parse_forum_item(node: Node) -> Result<ForumItem, Box<dyn Error>> {
     })
 }
 
-pub fn parse_forum(node: Node) -> Result<Forum, Box<dyn Error>> {
-    let title = node.find(Class("p-title-value")).next().ok_or("Title does not exist")?.text();
+pub fn parse_forum(node: Node) -> Result<Forum, VozError> {
+    let title = node.find(Class("p-title-value")).next().or_parse(".p-title-value", "Title does not exist")?.text();
     let sub_forums = node.find(Class("node")).filter_map(|x| parse_forum_item(x).ok()).collect::<Vec<ForumItem>>();
     let threads = node.find(Class("structItem--thread")).filter_map(|x| parse_thread(x).ok()).collect::<Vec<ThreadItem>>();
     Ok(Forum { title, sub_forums, threads })
 }
 
-pub fn parse_thread(node: Node) -> Result<ThreadItem, Box<dyn Error>> {
+pub fn parse_thread(node: Node) -> Result<ThreadItem, VozError> {
     let prefix = parse_prefix(node);
     let author = node.attr("data-author").unwrap_or_default().to_string();
     let classes = node.attr("class").unwrap_or_default();
     let id = classes
-                        .split(" ").find(|x| x.starts_with("js-threadListItem-")).ok_or("Cannot find thread id class")?
-                        .split("-").last().ok_or("Cannot find thread id")?.to_string();
-    let title = node.find(Class("structItem-title").child(Attr("class", ""))).next().ok_or("Cannot find thread title data")?.text().trimmed();
-    let replies = node.find(And(Class("pairs--justified"), Not(Class("structItem-minor"))).descendant(Name("dd"))).next().ok_or("Cannot find thread replies data")?.text();
+                        .split(" ").find(|x| x.starts_with("js-threadListItem-")).or_parse(".js-threadListItem-", "Cannot find thread id class")?
+                        .split("-").last().or_parse(".js-threadListItem-", "Cannot find thread id")?.to_string();
+    let title = node.find(Class("structItem-title").child(Attr("class", ""))).next().or_parse(".structItem-title a", "Cannot find thread title data")?.text().trimmed();
+    let replies = node.find(And(Class("pairs--justified"), Not(Class("structItem-minor"))).descendant(Name("dd"))).next().or_parse(".pairs--justified dd", "Cannot find thread replies data")?.text();
     // let views = node.find(And(Class("pairs--justified"), Class("structItem-minor")).descendant(Name("dd"))).next().unwrap().text();
-    let latest = node.find(Class("structItem-latestDate")).next().ok_or("Cannot find thread latest reply data")?.text().trimmed();
+    let latest = node.find(Class("structItem-latestDate")).next().or_parse(".structItem-latestDate", "Cannot find thread latest reply data")?.text().trimmed();
     let is_pinned = node.find(Class("structItem-status--sticky")).count() > 0;
     let is_read = !(classes.contains("is-unread"));
+    // Only lists spanning several forums (watched, find-threads, what's new) link the thread's forum
+    let forum_name = node.find(Class("structItem-parts").descendant(Name("a"))).find(|n| n.attr("href").is_some_and(|h| h.contains("/f/") || h.contains("/forums/"))).map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" "));
 
-    Ok(ThreadItem { id, prefix, title, is_pinned, is_read, replies, latest, author })
+    Ok(ThreadItem { id, prefix, title, is_pinned, is_read, replies, latest, author, forum_name })
+}
+
+pub fn parse_thread_list(node: Node) -> Result<ThreadList, VozError> {
+    let (current_page, total_page) = parse_page_nav(node);
+    let threads = node.find(Class("structItem--thread")).map(parse_thread).collect::<Result<Vec<ThreadItem>, VozError>>()?;
+    Ok(ThreadList { threads, current_page, total_page })
 }
 
 pub fn parse_prefix(node: Node) -> Option<ThreadPrefix> {
@@ -92,30 +101,312 @@ pub fn parse_prefix(node: Node) -> Option<ThreadPrefix> {
     
 }
 
-pub fn parse_login_form(node: Node) -> Result<LoginInfo, Box<dyn Error>> {
-    let form = node.find(Name("form")).next().ok_or("Login form should be existed")?;
-    let action = form.attr("action").ok_or("Form action not found")?.to_string();
-    let token = form.find(And(Name("input"), Attr("name", "_xfToken"))).next().ok_or("XF Token is not found")?.attr("value").unwrap_or("").to_string();
+pub fn parse_login_form(node: Node) -> Result<LoginInfo, VozError> {
+    let form = node.find(Name("form")).next().or_parse("form", "Login form should be existed")?;
+    let action = form.attr("action").or_parse("form[action]", "Form action not found")?.to_string();
+    let token = form.find(And(Name("input"), Attr("name", "_xfToken"))).next().or_parse("input[name=_xfToken]", "XF Token is not found")?.attr("value").unwrap_or("").to_string();
 
     Ok(LoginInfo { url: action, token: token })
 }
 
-pub fn parse_current_user(node: Node) -> Result<User, Box<dyn Error>> {
-    let div = node.find(Class("p-account").descendant(Class("avatar--xxs"))).next().ok_or("Not login yet")?;
+/// Reads the action, token and hidden inputs of an editor form (quick reply, new thread, conversation...).
+pub fn parse_editor_form(form: Node) -> Result<EditorForm, VozError> {
+    let url = form.attr("action").or_parse("form[action]", "Form action not found")?.to_string();
+    let hidden_fields = form.find(And(Name("input"), Attr("type", "hidden")))
+                        .filter_map(|n| n.attr("name").map(|k| (k.to_string(), n.attr("value").unwrap_or_default().to_string())))
+                        .collect::<HashMap<String, String>>();
+    let token = hidden_fields.get("_xfToken").cloned().or_parse("input[name=_xfToken]", "XF Token is not found")?;
+    let attachment_hash = hidden_fields.get("attachment_hash").cloned();
+
+    Ok(EditorForm { url, token, attachment_hash, hidden_fields })
+}
+
+/// Reads the prefixes offered by the prefix menu of a post thread form.
+pub fn parse_thread_prefixes(form: Node) -> Vec<ThreadPrefix> {
+    form.find(And(Name("select"), Attr("name", "prefix_id")).descendant(Name("option")))
+        .filter_map(|n| {
+            let id = n.attr("value").filter(|v| *v != "0")?.to_string();
+            let prefix_type = n.attr("data-prefix-class").unwrap_or_default().split("label--").last().unwrap_or_default().to_string();
+            Some(ThreadPrefix { id, title: n.text().trimmed(), prefix_type })
+        })
+        .collect()
+}
+
+/// Raw BBCode of an editor form. XenForo only puts it in the `<noscript>` fallback textarea, which
+/// html5ever keeps as text, so that text is parsed again.
+pub fn parse_editor_message(form: Node) -> Option<String> {
+    let textarea = And(Name("textarea"), Attr("name", "message"));
+    form.find(textarea).next().map(|n| n.text())
+        .or_else(|| form.find(Name("noscript")).find_map(|n| Document::from(n.text().as_str()).find(textarea).next().map(|t| t.text())))
+}
+
+/// Extracts the post id from XenForo post links such as `/p/123/`, `/posts/123/` or `/t/slug.1/post-123`.
+pub fn parse_post_id_from_url(url: &str) -> Option<String> {
+    ["/posts/", "/messages/", "/p/", "post-"].iter()
+        .find_map(|marker| url.rsplit_once(marker))
+        .map(|(_, rest)| rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>())
+        .filter(|id| !id.is_empty())
+}
+
+pub fn parse_current_user(node: Node) -> Result<CurrentUser, VozError> {
+    let div = node.find(Class("p-account").descendant(Class("avatar--xxs"))).next().ok_or(VozError::NotLoggedIn)?;
     let id = div.attr("data-user-id").unwrap_or("").to_string();
-    let info_node = div.find(Name("img")).next().ok_or("Not found avatar node")?;
+    let info_node = div.find(Name("img")).next().or_parse(".avatar--xxs img", "Not found avatar node")?;
     let name = info_node.attr("alt").unwrap_or("").to_string();
     let avatar = info_node.attr("src").unwrap_or("").to_string();
+    let badge = |class: &str| node.find(Class(class)).next().and_then(|n| n.attr("data-badge")).and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
+    let unread_alerts = badge("js-badge--alerts");
+    let unread_conversations = badge("js-badge--conversations");
+
+    Ok(CurrentUser { user: User { id, name, avatar }, unread_alerts, unread_conversations })
+}
+
+/// Parses the header and profile posts of a member page, the About tab is read by `parse_member_about`.
+pub fn parse_member_profile(node: Node) -> Result<MemberProfile, VozError> {
+    let header = node.find(Class("memberHeader")).next().or_parse(".memberHeader", "Not found member header")?;
+    let user_node = header.find(Class("memberHeader-name").descendant(Class("username"))).next().or_parse(".memberHeader-name .username", "Not found member name")?;
+    let id = user_node.attr("data-user-id").or_parse(".username[data-user-id]", "Not found member id")?.to_string();
+    let name = user_node.text().trimmed();
+    let avatar = parse_avatar_image(header.find(Class("memberHeader-avatar").descendant(Class("avatar"))).next(), name.clone());
+    let title = header.find(Class("userTitle")).next().map(|n| n.text().trimmed());
+    let blurb = |label: &str| header.find(Class("memberHeader-blurb").descendant(Name("dl")))
+                    .find(|n| n.find(Name("dt")).next().is_some_and(|dt| dt.text().trimmed() == label))
+                    .and_then(|n| n.find(Name("dd")).next())
+                    .map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" "));
+    let joined = blurb("Joined");
+    let last_seen = blurb("Last seen");
+    let stat = |label: &str| header.find(Class("memberHeader-stats").descendant(Name("dl")))
+                    .find(|n| n.find(Name("dt")).next().is_some_and(|dt| dt.text().trimmed() == label))
+                    .and_then(|n| n.find(Name("dd")).next())
+                    .map(|n| n.text().trimmed())
+                    .unwrap_or("0".to_string());
+    let message_count = stat("Messages");
+    let reaction_score = stat("Reaction score");
+    let location = header.find(Name("a")).find(|n| n.attr("href").is_some_and(|h| h.contains("location-info"))).map(|n| n.text().trimmed());
+    let banner = header.find(Class("memberProfileBanner")).next().and_then(|n| n.attr("style"))
+                    .and_then(|style| style.split_once("url(")).and_then(|(_, rest)| rest.split_once(')'))
+                    .map(|(url, _)| url.trim_matches(|c| c == '\'' || c == '"').to_string());
+    let is_following = header.find(Class("memberHeader-buttons").descendant(Name("a")))
+                    .find(|n| n.attr("href").is_some_and(|h| h.ends_with("/follow")))
+                    .map(|n| n.text().trimmed() == "Unfollow");
+    let profile_posts = node.find(And(Name("article"), Class("message--simple"))).map(parse_profile_post_activity).collect::<Result<Vec<ActivityItem>, VozError>>()?;
+
+    Ok(MemberProfile {
+        user: User { id, name, avatar },
+        title, joined, last_seen, message_count, reaction_score, location, banner,
+        about: None,
+        custom_fields: vec![],
+        is_following,
+        recent_activity: vec![],
+        profile_posts
+    })
+}
+
+/// Returns the "about" text and custom fields of a member About tab.
+pub fn parse_member_about(node: Node) -> (Option<String>, Vec<ProfileField>) {
+    let about = node.find(Class("block-row").child(Class("bbWrapper"))).next().map(|n| n.text().trimmed()).filter(|s| !s.is_empty());
+    let fields = node.find(And(Name("dl"), Class("pairs--customField"))).filter_map(|n| {
+        let id = n.attr("data-field")?.to_string();
+        let title = n.find(Name("dt")).next()?.text().trimmed();
+        let value = n.find(Name("dd")).next()?.text().split_whitespace().collect::<Vec<&str>>().join(" ");
+        Some(ProfileField { id, title, value })
+    }).collect::<Vec<ProfileField>>();
+    (about, fields)
+}
+
+pub fn parse_alerts(node: Node) -> Result<AlertList, VozError> {
+    let (current_page, total_page) = parse_page_nav(node);
+    let alerts = node.find(Attr("data-alert-id", ())).map(parse_alert).collect::<Result<Vec<Alert>, VozError>>()?;
+    Ok(AlertList { alerts, current_page, total_page })
+}
+
+pub fn parse_alert(node: Node) -> Result<Alert, VozError> {
+    let id = node.attr("data-alert-id").or_parse("[data-alert-id]", "Not found alert id")?.to_string();
+    let main = node.find(Class("contentRow-main")).next().or_parse(".contentRow-main", "Not found alert content")?;
+    let actor = main.find(Class("username")).next().map(|n| {
+        let name = n.text().trimmed();
+        let avatar = parse_avatar_image(node.find(Class("avatar")).next(), name.clone());
+        User { id: n.attr("data-user-id").unwrap_or_default().to_string(), name, avatar }
+    });
+    let link = main.find(Class("fauxBlockLink-blockLink")).next()
+                .or_else(|| main.find(And(Name("a"), Not(Class("username")))).next())
+                .and_then(|n| n.attr("href")).map(|s| s.to_string());
+    let message = main.children().filter(|n| !n.is(Class("contentRow-minor"))).map(|n| n.text()).collect::<String>()
+                .split_whitespace().collect::<Vec<&str>>().join(" ");
+    let kind = parse_alert_kind(main, link.as_deref(), &message);
+    let is_read = !node.attr("class").unwrap_or_default().contains("is-unread");
+    let time_node = node.find(Name("time")).next();
+    let time = time_node.map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
+
+    Ok(Alert { id, actor, kind, message, link, is_read, time, timestamp })
+}
+
+/// XenForo doesn't expose the alert action in the markup, reactions and thread/member links are told apart by their
+/// markup, quotes and mentions only by their English phrase. Anything else, e.g. a translated phrase, is `Other`.
+fn parse_alert_kind(main: Node, link: Option<&str>, message: &str) -> AlertKind {
+    let message = message.to_lowercase();
+    let link = link.unwrap_or_default();
+    if main.find(Class("reaction")).next().is_some() {
+        AlertKind::Reaction
+    } else if link.starts_with("/t/") && link.ends_with("/unread") {
+        AlertKind::Reply
+    } else if link.starts_with("/u/") || link.starts_with("/members/") {
+        AlertKind::Follow
+    } else if message.contains("quoted your") {
+        AlertKind::Quote
+    } else if message.contains("mentioned you") {
+        AlertKind::Mention
+    } else {
+        AlertKind::Other
+    }
+}
+
+/// Parses news feed rows and profile post lists into activity entries.
+pub fn parse_activities(node: Node, page: i64) -> Result<ActivityList, VozError> {
+    let mut activities = node.find(And(Name("article"), Class("message--simple"))).map(parse_profile_post_activity).collect::<Result<Vec<ActivityItem>, VozError>>()?;
+    let feed = node.find(Class("js-newsFeedTarget").child(Class("block-row"))).map(parse_activity).collect::<Result<Vec<ActivityItem>, VozError>>()?;
+    activities.extend(feed);
+    let before_id = node.find(Name("a")).filter_map(|n| n.attr("href")).find_map(|h| h.split_once("before_id="))
+                    .map(|(_, rest)| rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>());
+    let has_more = node.find(Class("pageNav-jump--next")).next().is_some() || before_id.is_some();
+    Ok(ActivityList { activities, current_page: page, has_more, before_id })
+}
+
+pub fn parse_activity(node: Node) -> Result<ActivityItem, VozError> {
+    let title = node.find(Class("contentRow-title")).next().or_parse(".contentRow-title", "Not found activity title")?;
+    let actor_node = title.find(Class("username")).next();
+    let actor = actor_node.map(|n| {
+        let name = n.text().split_whitespace().collect::<Vec<&str>>().join(" ");
+        let avatar = parse_avatar_image(node.find(Class("contentRow-figure").descendant(Class("avatar"))).next(), name.clone());
+        User { id: n.attr("data-user-id").unwrap_or_default().to_string(), name, avatar }
+    });
+    let target_node = title.children().filter(|n| n.is(Name("a")) && !n.is(Class("username"))).last();
+    let target = target_node.map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" "));
+    let link = target_node.and_then(|n| n.attr("href")).map(|s| s.to_string());
+    let target_index = target_node.map(|n| n.index()).unwrap_or(usize::MAX);
+    let actor_index = actor_node.map(|n| n.index());
+    let action = title.children().filter(|n| n.index() < target_index && Some(n.index()) != actor_index).map(|n| n.text()).collect::<String>()
+                .split_whitespace().collect::<Vec<&str>>().join(" ");
+    let snippet = node.find(Class("contentRow-snippet")).next().map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let time_node = node.find(Name("time")).next();
+    let time = time_node.map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
 
-    Ok(User { id, name, avatar })
+    Ok(ActivityItem { actor, action, target, link, snippet, time, timestamp })
 }
 
-pub fn parse_thread_detail(node: Node) -> Result<Thread, Box<dyn Error>> {
-    let title = node.find(Class("p-title-value")).next().ok_or("Not found thread title")?.text();
+/// A profile post as an activity, status updates have no target.
+pub fn parse_profile_post_activity(node: Node) -> Result<ActivityItem, VozError> {
+    let mut users = node.find(Class("attribution").descendant(Class("username")));
+    let actor_node = users.next().or_parse(".attribution .username", "Not found profile post author")?;
+    let name = actor_node.text().trimmed();
+    let avatar = parse_avatar_image(node.find(Class("message-avatar").descendant(Class("avatar"))).next(), name.clone());
+    let actor = Some(User { id: actor_node.attr("data-user-id").unwrap_or_default().to_string(), name, avatar });
+    let target = users.next().map(|n| n.text().trimmed());
+    let action = if target.is_some() { "posted on the profile of" } else { "updated their status" }.to_string();
+    let link = node.find(Class("message-footer").descendant(Name("a"))).filter_map(|n| n.attr("href")).find(|h| h.contains("/profile-posts/")).map(|s| s.to_string());
+    let snippet = node.find(Class("bbWrapper")).next().map(|n| n.text().trimmed()).unwrap_or_default();
+    let time_node = node.find(Name("time")).next();
+    let time = time_node.map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
+
+    Ok(ActivityItem { actor, action, target, link, snippet, time, timestamp })
+}
+
+/// Finds the message XenForo renders on error pages and rejected forms.
+pub fn parse_error_message(node: Node) -> Option<String> {
+    node.find(Class("blockMessage--error")).next()
+        .or_else(|| node.find(Class("p-body-pageContent").child(Class("blockMessage"))).next())
+        .map(|n| n.text().trimmed())
+        .filter(|s| !s.is_empty())
+}
+
+pub fn parse_thread_detail(node: Node) -> Result<Thread, VozError> {
+    let title_node = node.find(Class("p-title-value")).next().or_parse(".p-title-value", "Not found thread title")?;
+    let prefix = parse_prefix(title_node);
+    let title = title_node.children().filter(|n| !n.is(Class("labelLink")) && !n.is(Class("label-append"))).map(|n| n.text()).collect::<String>().trimmed();
+    let description = node.find(Class("p-description")).next();
+    let id = node.find(Class("lbContainer")).next().and_then(|n| n.attr("data-lb-id")).and_then(|s| s.strip_prefix("thread-")).map(|s| s.to_string())
+        .or_else(|| description.and_then(|d| d.find(Name("a")).filter_map(|a| a.attr("href")).find(|h| h.starts_with("/t/"))).and_then(|h| h.trim_end_matches('/').rsplit('.').next()).map(|s| s.to_string()))
+        .or_parse(".lbContainer[data-lb-id]", "Not found thread id")?;
+    // The starter's avatar is only on the page when one of their posts is
+    let starter = description.and_then(|d| d.find(Class("username")).next()).map(|n| {
+        let name = n.text().trimmed();
+        let id = n.attr("data-user-id").unwrap_or_default().to_string();
+        let avatar = parse_avatar_image(node.find(And(Class("avatar"), Attr("data-user-id", id.as_str()))).next(), name.clone());
+        User { id, avatar, name }
+    });
+    // The breadcrumb bar is repeated at the bottom of the page
+    let breadcrumbs = node.find(Class("p-breadcrumbs")).next().map(|n| n.find(Name("a")).filter_map(parse_breadcrumb).collect::<Vec<Breadcrumb>>()).unwrap_or_default();
+    // The watch button shows the phrase of its `data-sk-unwatch` state once the thread is watched
+    let is_watched = node.find(Name("a")).find(|n| n.attr("href").is_some_and(|h| h.ends_with("/watch")))
+                    .is_some_and(|n| n.attr("data-sk-unwatch").is_some_and(|unwatch| n.text().trimmed() == unwatch));
+    let (current_page, total_page) = parse_page_nav(node);
+    let can_reply = node.find(And(Name("form"), Class("js-quickReply"))).next().is_some();
+    let content = node.find(And(Name("article"), Class("js-post"))).map(parse_post).collect::<Result<Vec<Post>, VozError>>()?;
+    let reactions = node.find(Attr("id", "xfReactTooltipTemplate")).next().and_then(|n| parse_list_reactions(n.text()).ok()).unwrap_or_default();
+    let posts_html = node.find(And(Name("article"), Class("js-post"))).map(|x| x.html()).collect::<Vec<String>>().join("").replace("\n", "");
+    let is_bookmarked = content.first().filter(|p| p.position == 1).map(|p| p.is_bookmarked);
+    let poll = parse_poll(node);
+    Ok(Thread { id, prefix, title, starter, breadcrumbs, is_watched, is_bookmarked, current_page, total_page, can_reply, posts: content, posts_html, reactions, poll })
+}
+
+/// Breadcrumb links point either to a forum (`/f/slug.id/`) or to a category anchor on the index (`/#slug.id`).
+pub fn parse_breadcrumb(node: Node) -> Option<Breadcrumb> {
+    let link = node.attr("href")?;
+    let forum_type = if link.starts_with("/#") { "c" } else if link.contains("/f/") || link.contains("/forums/") { "f" } else { return None };
+    let id = link.trim_end_matches('/').rsplit('.').next().filter(|s| s.chars().all(|c| c.is_ascii_digit()))?;
+    Some(Breadcrumb { id: id.to_string(), title: node.text().trimmed(), forum_type: forum_type.to_string(), link: link.to_string() })
+}
+
+/// Parses the poll block shown above the first post, either as a vote form or as results.
+pub fn parse_poll(node: Node) -> Option<Poll> {
+    let container = node.find(Name("form")).find(|n| n.attr("class").is_some_and(|c| c.contains("js-pollContainer")))?;
+    let question = container.find(Class("block-header")).next()?.text().trimmed();
+    let number = |s: &str| s.chars().filter(|c| c.is_ascii_digit()).collect::<String>().parse::<i64>().ok();
+    let inputs = container.find(And(Name("input"), Attr("name", "responses[]"))).collect::<Vec<Node>>();
+    let options = if inputs.is_empty() {
+        container.find(Class("pollResult")).map(|n| PollOption {
+            id: n.find(Class("pollResult-votes").descendant(Name("a"))).next().and_then(|a| a.attr("href")).and_then(|h| h.split_once("response_id=")).map(|(_, id)| id.to_string()),
+            text: n.find(Class("pollResult-response")).next().map(|r| r.text().trimmed()).unwrap_or_default(),
+            votes: n.find(Class("pollResult-votes")).next().and_then(|v| number(&v.text())),
+            percentage: n.find(Class("pollResult-percentage")).next().and_then(|p| p.text().trimmed().trim_end_matches('%').parse::<f64>().ok()),
+            is_voted: n.attr("class").unwrap_or_default().contains("pollResult--voted")
+        }).collect::<Vec<PollOption>>()
+    } else {
+        inputs.iter().map(|n| PollOption {
+            id: n.attr("value").map(|s| s.to_string()),
+            text: n.parent().and_then(|l| l.find(Class("iconic-label")).next()).map(|l| l.text().trimmed()).unwrap_or_default(),
+            votes: None,
+            percentage: None,
+            is_voted: n.attr("checked").is_some()
+        }).collect::<Vec<PollOption>>()
+    };
+    let details = container.find(Name("li")).chain(container.find(Class("formRow-explain"))).map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).collect::<Vec<String>>();
+    let max_votes = if let Some(limit) = details.iter().find(|s| s.starts_with("You may select up to")) {
+        number(limit).unwrap_or(1)
+    } else if details.iter().any(|s| s.contains("Multiple votes are allowed")) || inputs.iter().any(|n| n.attr("type") == Some("checkbox")) {
+        0
+    } else {
+        1
+    };
+    let total_voters = details.iter().find(|s| s.starts_with("Total voters")).and_then(|s| number(s));
+    let time_node = container.find(Name("time")).next();
+    let close_date = time_node.map(|n| n.text().trimmed());
+    let close_timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
+    let is_closed = details.iter().any(|s| s.starts_with("Poll closed"));
+    let has_voted = options.iter().any(|o| o.is_voted);
+    let can_vote = !inputs.is_empty();
+
+    Some(Poll { question, options, max_votes, total_voters, close_date, close_timestamp, is_closed, has_voted, can_vote })
+}
+
+/// Returns the current and last page from the first `pageNav`, both "1" when there is no pagination.
+pub fn parse_page_nav(node: Node) -> (String, String) {
     let total_page_node = node.find(Class("pageNav-main")).next();
     let mut current_page = "1".to_string();
     let mut total_page = "1".to_string();
-    let can_reply = node.find(And(Name("form"), Class("js-quickReply"))).next().is_some();
     match total_page_node {
         Some(p_node) => {
             let children = p_node.find(Class("pageNav-page"));
@@ -128,26 +419,159 @@ pub fn parse_thread_detail(node: Node) -> Result<Thread, Box<dyn Error>> {
         },
         None => {}
     }
-    let content = node.find(And(Name("article"), Class("js-post"))).map(|x| parse_post(x).unwrap()).collect::<Vec<Post>>();
-    let reactions = node.find(Attr("id", "xfReactTooltipTemplate")).next().and_then(|n| parse_list_reactions(n.text()).ok()).unwrap_or_default();
-    let posts_html = node.find(And(Name("article"), Class("js-post"))).map(|x| x.html()).collect::<Vec<String>>().join("").replace("\n", "");
-    Ok(Thread { title, current_page, total_page, can_reply, posts: content, posts_html, prefix: None, reactions })
+    (current_page, total_page)
 }
 
-pub fn parse_post(node: Node) -> Result<Post, Box<dyn Error>> {
-    let mut post_info = node.find(Class("u-anchorTarget")).next().ok_or("Not found post info node")?.attr("id").unwrap_or_default().split("-").into_iter();
-    let post_type = post_info.next().map(|s| s.to_string()).ok_or("Not found post id")?;
-    let post_id = post_info.next().map(|s| s.to_string()).ok_or("Not found post id")?;
-    let user_node = node.find(Class("message-user")).next().ok_or("Not found user node for post")?;
-    let author_id = user_node.find(Class("avatar--m")).next().ok_or("Not found author id node")?.attr("data-user-id").ok_or("Not found author id")?.to_string();
-    let author_name = node.attr("data-author").ok_or("Not found author name attr")?.to_string().trimmed();
-    let avatar_node = user_node.find(Class("avatar--m")).next();
+pub fn parse_conversations(node: Node) -> Result<ConversationList, VozError> {
+    let (current_page, total_page) = parse_page_nav(node);
+    let conversations = node.find(Class("structItem--conversation")).map(parse_conversation_item).collect::<Result<Vec<ConversationItem>, VozError>>()?;
+    Ok(ConversationList { conversations, current_page, total_page })
+}
+
+pub fn parse_conversation_item(node: Node) -> Result<ConversationItem, VozError> {
+    let title_node = node.find(Class("structItem-title").child(Name("a"))).next().or_parse(".structItem-title a", "Cannot find conversation title")?;
+    let title = title_node.text().trimmed();
+    let id = title_node.attr("href").and_then(parse_content_id).or_parse(".structItem-title a[href]", "Cannot find conversation id")?;
+    let starter = node.attr("data-author").unwrap_or_default().to_string();
+    let participants = node.find(Class("listInline").descendant(Class("username"))).map(|n| n.text().trimmed()).collect::<Vec<String>>();
+    let replies = node.find(And(Class("pairs--justified"), Not(Class("structItem-minor"))).descendant(Name("dd"))).next().or_parse(".pairs--justified dd", "Cannot find conversation replies data")?.text().trimmed();
+    let latest_node = node.find(Class("structItem-cell--latest")).next();
+    let latest = latest_node.and_then(|n| n.find(Class("structItem-latestDate")).next()).map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let latest_author = latest_node.and_then(|n| n.find(Class("username")).next()).map(|n| n.text().trimmed());
+    let is_unread = node.attr("class").unwrap_or_default().contains("is-unread");
+
+    Ok(ConversationItem { id, title, starter, participants, replies, latest, latest_author, is_unread })
+}
+
+pub fn parse_conversation_detail(node: Node) -> Result<Conversation, VozError> {
+    let title = node.find(Class("p-title-value")).next().or_parse(".p-title-value", "Not found conversation title")?.text().trimmed();
+    let (current_page, total_page) = parse_page_nav(node);
+    let can_reply = node.find(And(Name("form"), Class("js-quickReply"))).next().is_some();
+    let participants = node.find(Class("p-body-sidebar").descendant(Class("contentRow"))).filter_map(|n| {
+        let user_node = n.find(Class("username")).next()?;
+        let name = user_node.text().trimmed();
+        let avatar = parse_avatar_image(n.find(Class("avatar")).next(), name.clone());
+        Some(User { id: user_node.attr("data-user-id").unwrap_or_default().to_string(), name, avatar })
+    }).collect::<Vec<User>>();
+    let messages = node.find(And(Name("article"), Class("js-message"))).map(parse_post).collect::<Result<Vec<Post>, VozError>>()?;
+    Ok(Conversation { title, current_page, total_page, can_reply, participants, messages })
+}
+
+/// Extracts the numeric id from XenForo content links such as `/conversations/slug.123/unread` or `/u/name.1/`.
+pub fn parse_content_id(url: &str) -> Option<String> {
+    url.split('/')
+        .filter_map(|segment| segment.rsplit('.').next())
+        .find(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(|id| id.to_string())
+}
+
+pub fn parse_bookmarks(node: Node) -> Result<BookmarkList, VozError> {
+    let (current_page, total_page) = parse_page_nav(node);
+    let bookmarks = node.find(Class("js-bookmark")).map(parse_bookmark).collect::<Result<Vec<Bookmark>, VozError>>()?;
+    Ok(BookmarkList { bookmarks, current_page, total_page })
+}
+
+pub fn parse_bookmark(node: Node) -> Result<Bookmark, VozError> {
+    let content_type = node.attr("data-content-type").or_parse(".js-bookmark[data-content-type]", "Not found bookmark content type")?.to_string();
+    let content_id = node.attr("data-content-id").or_parse(".js-bookmark[data-content-id]", "Not found bookmark content id")?.to_string();
+    let title_node = node.find(Class("contentRow-title").descendant(Name("a"))).next().or_parse(".contentRow-title a", "Not found bookmark title")?;
+    let title = title_node.text().split_whitespace().collect::<Vec<&str>>().join(" ");
+    let link = title_node.attr("href").unwrap_or_default().to_string();
+    let snippet = node.find(Class("contentRow-snippet")).next().map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let user_node = node.find(Class("contentRow-minor").descendant(Class("username"))).next();
+    let name = user_node.map(|n| n.text().trimmed()).unwrap_or_default();
+    let avatar = parse_avatar_image(node.find(Class("contentRow-figure").descendant(Class("avatar"))).next(), name.clone());
+    let author = User { id: user_node.and_then(|n| n.attr("data-user-id")).unwrap_or_default().to_string(), name, avatar };
+    let labels = node.find(Class("tagItem")).map(|n| n.text().trimmed()).collect::<Vec<String>>();
+    let note = node.find(Class("contentRow-spaced")).next().map(|n| n.text().trimmed()).filter(|s| !s.is_empty());
+    let time_node = node.find(Name("time")).next();
+    let time = time_node.map(|n| n.text().trimmed()).unwrap_or_default();
+    let timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
+
+    Ok(Bookmark { content_type, content_id, title, snippet, author, link, labels, note, time, timestamp })
+}
+
+/// Parses a search results page. The search id is read from the pagination links, so it is only known
+/// when there is more than one page.
+pub fn parse_search_results(node: Node) -> Result<SearchResults, VozError> {
+    let (current_page, total_page) = parse_page_nav(node);
+    let search_id = node.find(Class("pageNav").descendant(Name("a"))).filter_map(|n| n.attr("href")).find_map(parse_search_id);
+    let hits = node.find(Class("block-body").child(Class("block-row"))).filter_map(|n| parse_search_hit(n).ok()).collect::<Vec<SearchHit>>();
+    Ok(SearchResults { search_id, query: String::new(), options: SearchOptions::default(), hits, current_page, total_page })
+}
+
+/// Extracts the id from search result links such as `/search/51234567/?page=2&q=abc`.
+pub fn parse_search_id(url: &str) -> Option<String> {
+    url.split_once("/search/")
+        .map(|(_, rest)| rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>())
+        .filter(|id| !id.is_empty())
+}
+
+/// Extracts the find-new id XenForo redirects What's New filters to, e.g. `/whats-new/posts/4561237/page-2`.
+pub fn parse_find_new_id(url: &str) -> Option<String> {
+    url.split_once("/whats-new/")
+        .and_then(|(_, rest)| rest.split('/').nth(1))
+        .map(|segment| segment.chars().take_while(|c| c.is_ascii_digit()).collect::<String>())
+        .filter(|id| !id.is_empty())
+}
+
+pub fn parse_search_hit(node: Node) -> Result<SearchHit, VozError> {
+    let title_node = node.find(Class("contentRow-title").descendant(Name("a"))).next().or_parse(".contentRow-title a", "Cannot find search result title")?;
+    let link = title_node.attr("href").or_parse(".contentRow-title a[href]", "Cannot find search result link")?.to_string();
+    let (kind, id) = if link.contains("/t/") || link.contains("/threads/") {
+        (SearchHitKind::Thread, parse_content_id(&link).or_parse(".contentRow-title a[href]", "Cannot find thread id")?)
+    } else {
+        (SearchHitKind::Post, parse_post_id_from_url(&link).or_parse(".contentRow-title a[href]", "Search result is neither a thread nor a post")?)
+    };
+    let title = title_node.children().filter(|n| !n.is(Class("label")) && !n.is(Class("label-append"))).map(|n| n.text()).collect::<String>()
+                .split_whitespace().collect::<Vec<&str>>().join(" ");
+    let snippet = node.find(Class("contentRow-snippet")).next().map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let minor = node.find(Class("contentRow-minor")).next().or_parse(".contentRow-minor", "Cannot find search result details")?;
+    let user_node = minor.find(Class("username")).next();
+    let name = user_node.map(|n| n.text().trimmed()).or_else(|| node.attr("data-author").map(|s| s.to_string())).unwrap_or_default();
+    let avatar = parse_avatar_image(node.find(Class("contentRow-figure").descendant(Class("avatar"))).next(), name.clone());
+    let author = User { id: user_node.and_then(|n| n.attr("data-user-id")).unwrap_or_default().to_string(), name, avatar };
+    let forum_node = minor.find(Name("a")).find(|n| n.attr("href").is_some_and(|h| h.contains("/f/") || h.contains("/forums/")));
+    let forum_id = forum_node.and_then(|n| n.attr("href")).and_then(parse_content_id);
+    let forum_name = forum_node.map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" "));
+    let replies = minor.find(Name("li")).find_map(|n| n.text().trimmed().strip_prefix("Replies:").map(|s| s.trim().to_string()));
+    let time_node = minor.find(Name("time")).next();
+    let time = time_node.map(|n| n.text().trimmed()).unwrap_or_default();
+    let timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
+
+    Ok(SearchHit { kind, id, title, snippet, author, forum_id, forum_name, replies, link, time, timestamp })
+}
+
+pub fn parse_post(node: Node) -> Result<Post, VozError> {
+    let mut post_info = node.find(Class("u-anchorTarget")).next().or_parse(".u-anchorTarget", "Not found post info node")?.attr("id").unwrap_or_default().split("-").into_iter();
+    let post_type = post_info.next().map(|s| s.to_string()).or_parse(".u-anchorTarget[id]", "Not found post type")?;
+    let post_id = post_info.next().map(|s| s.to_string()).or_parse(".u-anchorTarget[id]", "Not found post id")?;
+    // Posts of ignored members are collapsed to a notice without the author block and content
+    let is_ignored = node.attr("class").unwrap_or_default().contains("is-ignored") || node.find(Class("messageNotice--ignored")).next().is_some();
+    let user_node = node.find(Class("message-user")).next();
+    let author_id = match user_node {
+        Some(user_node) => user_node.find(Class("avatar--m")).next().or_parse(".avatar--m", "Not found author id node")?.attr("data-user-id").or_parse(".avatar--m[data-user-id]", "Not found author id")?.to_string(),
+        None if is_ignored => node.find(Attr("data-user-id", ())).next().and_then(|n| n.attr("data-user-id")).unwrap_or_default().to_string(),
+        None => return Err(VozError::parse(".message-user", "Not found user node for post"))
+    };
+    let author_name = node.attr("data-author").or_parse("article[data-author]", "Not found author name attr")?.to_string().trimmed();
+    let avatar_node = user_node.and_then(|n| n.find(Class("avatar--m")).next());
     let author_avatar = parse_avatar_image(avatar_node, author_name.clone());
-    let created = node.find(Class("message-attribution-main").descendant(Name("time"))).next().ok_or("Not found created node")?.text().trimmed();
+    // The avatar or the ignored notice links the profile, XenForo resolves `/u/{id}/` to it as well
+    let author_link = node.find(And(Name("a"), Attr("data-user-id", author_id.as_str()))).find_map(|n| n.attr("href")).map(|s| s.to_string())
+        .or_else(|| Some(format!("/u/{author_id}/"))).filter(|_| !author_id.is_empty());
+    let created_node = node.find(Class("message-attribution-main").descendant(Name("time"))).next();
+    let created = if is_ignored { created_node.map(|n| n.text().trimmed()).unwrap_or_default() } else { created_node.or_parse(".message-attribution-main time", "Not found created node")?.text().trimmed() };
     let last_edited: Option<String> = node.find(Class("message-lastEdit").descendant(Name("time"))).next().map(|n| n.text().trimmed());
     let reactions: Option<ReactionSummary> = node.find(And(Class("reactionsBar"), Class("is-active"))).next().and_then(|n| parse_reactions(n));
-    let content_node = node.find(Class("message-body").descendant(Class("bbWrapper"))).next().ok_or("Not found content node")?;
-    let html_content: String = content_node.html();
+    let content_node = node.find(Class("message-body").descendant(Class("bbWrapper"))).next();
+    let html_content: String = match content_node {
+        Some(content_node) => content_node.html(),
+        None if is_ignored => String::new(),
+        None => return Err(VozError::parse(".message-body .bbWrapper", "Not found content node"))
+    };
+    // A block the parser cannot read should not make the whole page fail, `html_content` still has it
+    let contents = content_node.map(parse_post_contents).transpose()?.unwrap_or_default();
     let can_edit = node.find(Class("actionBar-action--edit")).count() > 0;
     let can_delete = node.find(Class("actionBar-action--delete")).count() > 0;
     let can_react = node.find(Class("actionBar-action--reaction")).count() > 0;
@@ -155,33 +579,79 @@ pub fn parse_post(node: Node) -> Result<Post, Box<dyn Error>> {
     let can_multiple_quote = node.find(Class("actionBar-action--reply")).count() > 0;
     let is_reacted_to = node.find(Class("has-reaction")).count() > 0;
     let visitor_reaction_id = node.find(Class("has-reaction")).next().and_then(|n| n.attr("data-reaction-id")).and_then(|s| s.parse::<i64>().ok());
-    let position = node.find(Class("message-attribution-opposite--list").descendant(Name("li"))).last().and_then(|n| n.text().replace("#", "").parse::<i64>().ok()).unwrap_or(0);
-    Ok(Post { post_id, post_type, author_id, author_name, author_avatar, created, last_edited, reactions, html_content, warning_message: None, position, can_edit, can_delete, can_react, is_reacted_to, visitor_reaction_id, can_reply, can_multiple_quote })
+    let position = node.find(Class("message-attribution-opposite--list").descendant(Name("li"))).last().and_then(|n| n.text().trim().replace("#", "").parse::<i64>().ok()).unwrap_or(0);
+    let is_bookmarked = node.find(And(Class("bookmarkLink"), Class("is-bookmarked"))).count() > 0;
+    Ok(Post { post_id, post_type, author_id, author_name, author_avatar, author_link, created, last_edited, reactions, html_content, contents, warning_message: None, position, can_edit, can_delete, can_react, is_reacted_to, visitor_reaction_id, can_reply, can_multiple_quote, is_bookmarked, is_ignored })
+}
+
+/// Members the visitor ignores, from the account "Ignoring" page.
+pub fn parse_ignored_members(node: Node) -> Result<Vec<User>, VozError> {
+    node.find(Class("block-row").descendant(Class("contentRow"))).map(|n| {
+        let user_node = n.find(Class("contentRow-header").descendant(Class("username"))).next().or_parse(".contentRow-header .username", "Not found ignored member")?;
+        let id = user_node.attr("data-user-id").or_parse(".username[data-user-id]", "Not found ignored member id")?.to_string();
+        let name = user_node.text().trimmed();
+        let avatar = parse_avatar_image(n.find(Class("contentRow-figure").descendant(Class("avatar"))).next(), name.clone());
+        Ok(User { id, name, avatar })
+    }).collect()
 }
 
-pub fn parse_post_contents(node: Node) -> Result<Vec<ContentType>, Box<dyn Error>> {
-    let mut content_string: String = "".to_string();
+/// Parses the "who reacted" overlay of a post.
+pub fn parse_post_reactions(node: Node, page: i64) -> Result<PostReactions, VozError> {
+    let users = node.find(Class("block-row").descendant(Class("contentRow")))
+                    .map(parse_reacted_user)
+                    .collect::<Result<Vec<ReactedUser>, VozError>>()?;
+    let next_page = format!("page={}", page + 1);
+    let has_more = node.find(Class("block-footer").descendant(Name("a"))).any(|n| n.attr("href").is_some_and(|h| h.contains(&next_page)))
+                    || node.find(Class("pageNav-jump--next")).next().is_some();
+    Ok(PostReactions { users, current_page: page, has_more })
+}
+
+fn parse_reacted_user(node: Node) -> Result<ReactedUser, VozError> {
+    let user_node = node.find(Class("contentRow-header").descendant(Class("username"))).next().or_parse(".contentRow-header .username", "Not found reacted user")?;
+    let id = user_node.attr("data-user-id").or_parse(".username[data-user-id]", "Not found reacted user id")?.to_string();
+    let name = user_node.text().trimmed();
+    let avatar = parse_avatar_image(node.find(Class("contentRow-figure").descendant(Class("avatar"))).next(), name.clone());
+    let reaction_node = node.find(And(Class("reaction"), Attr("data-reaction-id", ()))).next().or_parse(".reaction[data-reaction-id]", "Not found reaction")?;
+    let reaction_id = reaction_node.attr("data-reaction-id").and_then(|s| s.parse::<i64>().ok()).or_parse(".reaction[data-reaction-id]", "Invalid reaction id")?;
+    let image = reaction_node.find(Name("img")).next();
+    let icon = image.and_then(|n| n.attr("src")).unwrap_or_default().to_string();
+    let title = image.and_then(|n| n.attr("title")).unwrap_or_default().to_string();
+    let time_node = node.find(Name("time")).next();
+    let time = time_node.map(|n| n.text().split_whitespace().collect::<Vec<&str>>().join(" ")).unwrap_or_default();
+    let timestamp = time_node.and_then(|n| n.attr("data-time")).and_then(|s| s.parse::<i64>().ok());
+
+    Ok(ReactedUser { user: User { id, name, avatar }, reaction: Reaction { id: reaction_id, icon, title }, time, timestamp })
+}
+
+pub fn parse_post_contents(node: Node) -> Result<Vec<ContentType>, VozError> {
     let mut results: Vec<ContentType> = vec![];
+    let mut inlines: Vec<Inline> = vec![];
     for x in node.children() {
-        let content_type = parse_content(x)?;
-        if content_type.is_some() {
-            if !content_string.trimmed().is_empty() && content_string.trimmed().ne("<br>") {
-                results.push(ContentType::Html { content: content_string.clone() });
-                content_string.clear();
-            }
-            results.push(content_type.unwrap())
-        } else if x.name().is_some_and(|s| s.eq("script")) {
-
-        } else {
-            content_string += x.html().trimmed().as_str();
+        let blocks = match parse_content(x) {
+            Ok(Some(content)) => Ok(Some(vec![content])),
+            Ok(None) => parse_block(x),
+            Err(error) => Err(error)
+        };
+        // A block that doesn't parse is kept as html instead of losing the rest of the post
+        match blocks.unwrap_or_else(|_| Some(vec![ContentType::Html { content: x.html() }])) {
+            Some(blocks) => {
+                push_paragraph(&mut results, &mut inlines);
+                results.extend(blocks);
+            },
+            None => inlines.extend(parse_inline(x))
         }
     }
-    if !content_string.is_empty() && content_string.ne("<br>") {
-        results.push(ContentType::Html { content: content_string.clone() });
-    }
+    push_paragraph(&mut results, &mut inlines);
     Ok(results)
 }
 
+fn push_paragraph(results: &mut Vec<ContentType>, inlines: &mut Vec<Inline>) {
+    let content = normalize_inlines(std::mem::take(inlines));
+    if !content.is_empty() {
+        results.push(ContentType::Paragraph { content });
+    }
+}
+
 fn parse_avatar_image(node: Option<Node>, username: String) -> String {
     let letter = username.replace(" ", "+");
     let base = format!("https://ui-avatars.com/api/?length=1&rounded=true&name={letter}");
@@ -216,11 +686,140 @@ mod tests {
         let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
         let result = parse_post_contents(document.find(Class("message-body").descendant(Class("bbWrapper"))).next().unwrap()).unwrap();
         // assert_eq!(result.len(), 5);
-        if let ContentType::CodeBlock { .. } = result[2] {
+        if let ContentType::Heading { level, content } = &result[0] {
+            assert_eq!(*level, 1);
+            assert_eq!(content, &vec![Inline::Text { text: "Heading 1".to_string() }]);
+        } else {
+            panic!("Expected heading, got {:?}", result[0]);
+        }
+        if let ContentType::Paragraph { content } = &result[3] {
+            assert_eq!(content[0], Inline::Text { text: "This is the body with an icon ".to_string() });
+            assert!(matches!(&content[1], Inline::Smilie { code, .. } if code == ";)"));
+            assert_eq!(content[2], Inline::LineBreak);
+            assert_eq!(content.len(), 4);
+        } else {
+            panic!("Expected paragraph, got {:?}", result[3]);
+        }
+        assert!(matches!(result[4], ContentType::HorizontalRule));
+        assert!(matches!(&result[5], ContentType::QuoteBlock { author_id: None, author_name: None, post_id: None, content } if matches!(content[1], ContentType::Image { .. })));
+        if let ContentType::CodeBlock { .. } = result[6] {
             assert!(true);
         } else {
             assert!(false);
         }
+        if let ContentType::Embeded { site, title, link } = &result[8] {
+            assert_eq!(site, "facebook");
+            assert_eq!(title, "ChuyencuaHaNoi/posts/pfbid0GUskDC3GZPQjiVwWJXV7AFK5dW6LDwUDV44AviCYuc3quVuiYLqqeuMDPMgwP8oPl");
+            assert!(link.is_none());
+        } else {
+            panic!("Expected embed, got {:?}", result[8]);
+        }
+        assert!(!result.iter().any(|c| matches!(c, ContentType::Html { .. })));
+    }
+
+    #[test]
+    fn test_table() {
+        let path = Path::new("resources/tests/table.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+        let result = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let ContentType::QuoteBlock { content, .. } = &result[1] else { panic!("Expected quote, got {:?}", result[1]) };
+        assert_eq!(content.len(), 3);
+        let ContentType::Table { header, rows } = &content[1] else { panic!("Expected table, got {:?}", content[1]) };
+        assert!(header.is_none());
+        assert_eq!(rows.iter().map(|r| r.len()).collect::<Vec<usize>>(), vec![1, 1]);
+        // Linked images have no lightbox wrapper
+        assert_eq!(rows[0][0].content.len(), 2);
+        assert!(matches!(&rows[0][0].content[0], ContentType::Image { src } if src == "https://image.tienphong.vn/w645/Uploaded/2023/ofh-fdazstgk/2023_12_23/ox-zung-1638543524-2720622726766077459-2325290822-8058.jpg"));
+        assert!(matches!(&rows[1][0].content[0], ContentType::Paragraph { content } if content == &vec![Inline::Text { text: "Seo Won Jeong có hơn 55 triệu người theo dõi trên TikTok.".to_string() }]));
+        assert_eq!((rows[1][0].rowspan, rows[1][0].colspan), (1, 1));
+    }
+
+    #[test]
+    fn test_table_spans() {
+        let html = r#"<div class="bbWrapper"><div class="bbTable"><table style="width: 100%"><tbody>
+            <tr><th>Kit</th><th>Bus</th><th>Giá</th></tr>
+            <tr><td rowspan="2"><b>Kingston Fury Beast</b> 32GB</td><td>5600</td><td>2.750.000</td></tr>
+            <tr><td>6000</td><td>2.990.000</td></tr>
+            <tr><td colspan="3">Xem thêm <a href="https://voz.vn/f/overclocking-cooling-modding.6/" class="link link--internal">tại đây</a></td></tr>
+            </tbody></table></div></div>"#;
+        let document = Document::from(html);
+        let result = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+
+        let ContentType::Table { header, rows } = &result[0] else { panic!("Expected table, got {:?}", result[0]) };
+        let header = header.as_ref().unwrap();
+        assert_eq!(header.len(), 3);
+        assert!(header.iter().all(|c| c.is_header));
+        assert!(matches!(&header[2].content[0], ContentType::Paragraph { content } if content == &vec![Inline::Text { text: "Giá".to_string() }]));
+        assert_eq!(rows.iter().map(|r| r.len()).collect::<Vec<usize>>(), vec![3, 2, 1]);
+        assert_eq!((rows[0][0].rowspan, rows[0][0].colspan), (2, 1));
+        assert!(matches!(&rows[0][0].content[0], ContentType::Paragraph { content } if matches!(content[0], Inline::Bold { .. })));
+        assert_eq!((rows[2][0].rowspan, rows[2][0].colspan), (1, 3));
+        assert!(matches!(&rows[2][0].content[0], ContentType::Paragraph { content } if matches!(&content[1], Inline::Link { url, .. } if url == "https://voz.vn/f/overclocking-cooling-modding.6/")));
+    }
+
+    #[test]
+    fn test_inline() {
+        let path = Path::new("resources/tests/inline.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+        let result = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+        assert_eq!(result.len(), 1);
+
+        let ContentType::Paragraph { content } = &result[0] else { panic!("Expected paragraph, got {:?}", result[0]) };
+        let text = |s: &str| Inline::Text { text: s.to_string() };
+        assert_eq!(content[0], Inline::Mention { user_id: "1234567".to_string(), name: "bboyviet".to_string() });
+        assert_eq!(content[2], Inline::Link { url: "https://voz.vn/t/hoi-ve-ban-phim-co.880001/".to_string(), content: vec![text("thread này")] });
+        assert_eq!(content[4], Inline::Smilie { code: ":D".to_string(), src: "https://data.voz.vn/styles/next/xenforo/smilies/popo/big_smile.png?v=01".to_string() });
+        assert_eq!(content[5], Inline::LineBreak);
+        assert_eq!(content[6..13], [
+            Inline::Bold { content: vec![text("Đậm")] }, text(", "),
+            Inline::Italic { content: vec![text("nghiêng")] }, text(", "),
+            Inline::Underline { content: vec![text("gạch chân")] }, text(", "),
+            Inline::Strike { content: vec![text("gạch ngang")] }
+        ]);
+        assert_eq!(content[14], Inline::Color { color: "rgb(184, 49, 47)".to_string(), content: vec![text("Chữ đỏ")] });
+        assert_eq!(content[16], Inline::Size { size: "22px".to_string(), content: vec![text("chữ to")] });
+        assert_eq!(content[18], Inline::Color { color: "#0000ff".to_string(), content: vec![
+            Inline::Size { size: "9px".to_string(), content: vec![Inline::Bold { content: vec![text("nhỏ xanh")] }] }
+        ] });
+        assert_eq!(content[20..], [text("Chạy lệnh "), Inline::InlineCode { code: "cargo build --release".to_string() }, text(" trước")]);
+    }
+
+    #[test]
+    fn test_mixed_blocks() {
+        let image = r#"<div class="bbImageWrapper  js-lbImage" title="" data-src="https://i.imgur.com/abc.png"><img src="https://i.imgur.com/abc.png" data-url="https://i.imgur.com/abc.png" class="bbImage" alt="" loading="lazy"></div>"#;
+        let html = format!(concat!(
+            r#"<div class="bbWrapper"><ul><li data-xf-list-type="ul">Ảnh đây {image}</li><li data-xf-list-type="ul">Dòng hai</li></ul>"#,
+            r#"<div style="text-align: center">Căn giữa<br>{image}</div>"#,
+            r#"<a href="https://imgur.com/abc" target="_blank" class="link link--external">{image}</a>"#,
+            r#"<iframe src="https://www.youtube.com/embed/abc" width="560" height="315"></iframe></div>"#
+        ), image = image);
+        let document = Document::from(html.as_str());
+        let result = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+        let is_image = |c: &ContentType| matches!(c, ContentType::Image { src } if src == "https://i.imgur.com/abc.png");
+        let text = |s: &str| Inline::Text { text: s.to_string() };
+
+        let ContentType::List { items, .. } = &result[0] else { panic!("Expected list, got {:?}", result[0]) };
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[0][0], ContentType::Paragraph { content } if content == &vec![text("Ảnh đây")]));
+        assert!(is_image(&items[0][1]));
+        assert!(matches!(&result[1], ContentType::Paragraph { content } if content == &vec![text("Căn giữa")]));
+        assert!(is_image(&result[2]));
+        assert!(is_image(&result[3]));
+        assert!(matches!(&result[4], ContentType::Html { content } if content.starts_with("<iframe")));
+        assert_eq!(result.len(), 5);
+
+        // An unfurl without its url only falls back for that block
+        let html = r#"<div class="bbWrapper">Trước<div class="bbCodeBlock bbCodeBlock--unfurl js-unfurl"></div>Sau</div>"#;
+        let document = Document::from(html);
+        let result = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+        assert_eq!(result.len(), 3);
+        assert!(matches!(&result[0], ContentType::Paragraph { content } if content == &vec![text("Trước")]));
+        assert!(matches!(&result[1], ContentType::Html { content } if content.contains("bbCodeBlock--unfurl")));
+        assert!(matches!(&result[2], ContentType::Paragraph { content } if content == &vec![text("Sau")]));
     }
 
     #[test]
@@ -261,7 +860,7 @@ mod tests {
         let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
         
         let result = parse_thread(document.nth(3).unwrap()).unwrap();
-        let expectation = ThreadItem { id: "73313".to_string(), prefix: Some(ThreadPrefix { id: "17".to_string(), title: "kiến thức".to_string(), prefix_type: "royalBlue".to_string() }), title: "[Dịch] Hướng dẫn OC DDR4".to_string(), is_pinned: true, is_read: false, replies: "1K".to_string(), latest: "Yesterday at 11:03 AM".to_string(), author: "troll159753".to_string() };
+        let expectation = ThreadItem { id: "73313".to_string(), prefix: Some(ThreadPrefix { id: "17".to_string(), title: "kiến thức".to_string(), prefix_type: "royalBlue".to_string() }), title: "[Dịch] Hướng dẫn OC DDR4".to_string(), is_pinned: true, is_read: false, replies: "1K".to_string(), latest: "Yesterday at 11:03 AM".to_string(), author: "troll159753".to_string(), forum_name: None };
         assert_eq!(result, expectation);
     }
 
@@ -275,6 +874,45 @@ mod tests {
         assert_eq!(result.sub_forums.len(), 2);
     }
 
+    #[test]
+    fn test_watched_threads() {
+        let path = Path::new("resources/tests/watched_threads.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_thread_list(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.current_page, "2");
+        assert_eq!(result.total_page, "3");
+        assert_eq!(result.threads.len(), 2);
+        assert_eq!(result.threads[0].id, "896639");
+        assert_eq!(result.threads[0].title, "TikToker 5,5 triệu người theo dõi bị bắt");
+        assert!(!result.threads[0].is_read);
+        assert_eq!(result.threads[1].prefix.as_ref().map(|p| p.id.as_str()), Some("4"));
+        assert_eq!(result.threads[1].replies, "42");
+        assert_eq!(result.threads[0].forum_name, Some("Chuyện trò linh tinh™".to_string()));
+    }
+
+    #[test]
+    fn test_find_threads() {
+        let path = Path::new("resources/tests/find_threads.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_thread_list(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.current_page, "1");
+        assert_eq!(result.total_page, "2");
+        assert_eq!(result.threads.len(), 1);
+        let thread = &result.threads[0];
+        assert_eq!(thread.id, "73313");
+        assert_eq!(thread.title, "[Dịch] Hướng dẫn OC DDR4");
+        assert_eq!(thread.prefix.as_ref().map(|p| p.title.as_str()), Some("kiến thức"));
+        assert!(thread.is_pinned);
+        assert!(!thread.is_read);
+        assert_eq!(thread.replies, "1K");
+        assert_eq!(thread.author, "troll159753");
+        assert_eq!(thread.forum_name, Some("Overclocking & Cooling & Modding".to_string()));
+    }
+
     #[test]
     fn test_login_form() {
         let path = Path::new("resources/tests/login_form.html");
@@ -293,8 +931,339 @@ mod tests {
         let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
         
         let result = parse_current_user(document.nth(3).unwrap()).unwrap();
-        assert_eq!(result.id, "1932329");
-        assert_eq!(result.avatar, "https://data.voz.vn/avatars/s/1932/1932329.jpg?1700878980");
+        assert_eq!(result.user.id, "1932329");
+        assert_eq!(result.user.avatar, "https://data.voz.vn/avatars/s/1932/1932329.jpg?1700878980");
+        assert_eq!(result.unread_alerts, 0);
+        assert_eq!(result.unread_conversations, 0);
+
+        let path = Path::new("resources/tests/current_user_unread.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_current_user(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.user.name, "cherry-picked");
+        assert_eq!(result.unread_alerts, 14);
+        assert_eq!(result.unread_conversations, 2);
+    }
+
+    #[test]
+    fn test_member_profile() {
+        let path = Path::new("resources/tests/member.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_member_profile(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.user.id, "1234567");
+        assert_eq!(result.user.name, "bboyviet");
+        assert_eq!(result.user.avatar, "https://data.voz.vn/avatars/l/1234/1234567.jpg?1690000000");
+        assert_eq!(result.title, Some("Senior Member".to_string()));
+        assert_eq!(result.joined, Some("Oct 3, 2020".to_string()));
+        assert_eq!(result.last_seen, Some("Today at 4:05 PM".to_string()));
+        assert_eq!(result.message_count, "12,345");
+        assert_eq!(result.reaction_score, "6,789");
+        assert_eq!(result.location, Some("Hà Nội".to_string()));
+        assert_eq!(result.banner, Some("https://data.voz.vn/profile_banners/l/1234/1234567.jpg?1695000000".to_string()));
+        assert_eq!(result.is_following, Some(true));
+        assert_eq!(result.profile_posts.len(), 1);
+        assert_eq!(result.profile_posts[0].target, Some("bboyviet".to_string()));
+
+        let path = Path::new("resources/tests/member_about.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+        let (about, fields) = parse_member_about(document.nth(0).unwrap());
+        assert_eq!(about, Some("Thích bàn phím cơ và Rust.".to_string()));
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].id, "occupation");
+        assert_eq!(fields[0].title, "Occupation");
+        assert_eq!(fields[1].value, "example.com");
+    }
+
+    #[test]
+    fn test_alerts() {
+        let path = Path::new("resources/tests/alerts.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_alerts(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.total_page, "3");
+        assert_eq!(result.alerts.len(), 4);
+        let quote = &result.alerts[0];
+        assert_eq!(quote.id, "98765431");
+        assert_eq!(quote.kind, AlertKind::Quote);
+        assert_eq!(quote.actor.as_ref().map(|u| u.name.as_str()), Some("bboyviet"));
+        assert_eq!(quote.link, Some("/p/29543120/".to_string()));
+        assert_eq!(quote.message, "bboyviet quoted your post in the thread TikToker 5,5 triệu người theo dõi bị bắt.");
+        assert_eq!(quote.time, "Today at 4:05 PM");
+        assert!(!quote.is_read);
+        assert_eq!(result.alerts[1].kind, AlertKind::Reaction);
+        assert_eq!(result.alerts[2].kind, AlertKind::Mention);
+        assert_eq!(result.alerts[3].kind, AlertKind::Reply);
+        assert!(result.alerts[3].is_read);
+
+        let html = r#"<ul><li data-alert-id="98765400" class="alert js-alert is-unread"><div class="contentRow-main">
+            <a href="/u/bboyviet.1234567/" class="username" data-user-id="1234567">bboyviet</a>
+            đã trích dẫn bài viết của bạn trong chủ đề <a href="/p/29543120/" class="fauxBlockLink-blockLink">Hỏi về bàn phím cơ</a>.
+            </div></li>
+            <li data-alert-id="98765399" class="alert js-alert"><div class="contentRow-main">
+            <a href="/u/heroxrust.1948176/" class="username" data-user-id="1948176">heroxrust</a>
+            đã trả lời chủ đề <a href="/t/hoi-ve-ban-phim-co.880001/unread" class="fauxBlockLink-blockLink">Hỏi về bàn phím cơ</a>.
+            </div></li></ul>"#;
+        let document = Document::from(html);
+        let result = parse_alerts(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.alerts[0].kind, AlertKind::Other);
+        assert_eq!(result.alerts[1].kind, AlertKind::Reply);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let path = Path::new("resources/tests/bookmarks.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_bookmarks(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.total_page, "2");
+        assert_eq!(result.bookmarks.len(), 2);
+        let post = &result.bookmarks[0];
+        assert_eq!(post.content_type, "post");
+        assert_eq!(post.content_id, "29542846");
+        assert_eq!(post.title, "Post in thread 'TikToker 5,5 triệu người theo dõi bị bắt'");
+        assert_eq!(post.labels, vec!["tin tức", "hay"]);
+        assert_eq!(post.note, Some("Đọc lại sau".to_string()));
+        assert_eq!(post.author.id, "1234567");
+        let thread = &result.bookmarks[1];
+        assert_eq!(thread.content_type, "thread");
+        assert!(thread.labels.is_empty());
+        assert_eq!(thread.note, None);
+    }
+
+    #[test]
+    fn test_search_results() {
+        let path = Path::new("resources/tests/search.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_search_results(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.search_id, Some("51234567".to_string()));
+        assert_eq!(result.total_page, "2");
+        assert_eq!(result.hits.len(), 2);
+        let thread = &result.hits[0];
+        assert_eq!(thread.kind, SearchHitKind::Thread);
+        assert_eq!(thread.id, "880001");
+        assert_eq!(thread.title, "Hỏi về bàn phím cơ");
+        assert_eq!(thread.snippet, "Mọi người cho hỏi bàn phím cơ nào gõ êm dưới 2 triệu...");
+        assert_eq!(thread.author.id, "1932329");
+        assert_eq!(thread.forum_id, Some("33".to_string()));
+        assert_eq!(thread.forum_name, Some("Bàn phím, chuột, gear".to_string()));
+        assert_eq!(thread.replies, Some("42".to_string()));
+        assert_eq!(thread.timestamp, Some(1703038200));
+        let post = &result.hits[1];
+        assert_eq!(post.kind, SearchHitKind::Post);
+        assert_eq!(post.id, "29012345");
+        assert_eq!(post.author.name, "bboyviet");
+        assert_eq!(post.replies, None);
+        assert_eq!(post.time, "Dec 21, 2023");
+    }
+
+    #[test]
+    fn test_latest_activity() {
+        let path = Path::new("resources/tests/latest_activity.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_activities(document.nth(0).unwrap(), 1).unwrap();
+        assert!(result.has_more);
+        assert_eq!(result.before_id, Some("71234560".to_string()));
+        assert_eq!(result.activities.len(), 2);
+        let reply = &result.activities[0];
+        assert_eq!(reply.actor.as_ref().map(|u| u.id.as_str()), Some("1234567"));
+        assert_eq!(reply.action, "replied to the thread");
+        assert_eq!(reply.target, Some("TikToker 5,5 triệu người theo dõi bị bắt".to_string()));
+        assert_eq!(reply.link, Some("/t/tiktoker-55-8-trieu-nguoi-theo-doi-bi-bat.896639/post-29543120".to_string()));
+        assert_eq!(reply.snippet, "Đu idol thì cũng phải có giới hạn chứ nhỉ");
+        let reaction = &result.activities[1];
+        assert_eq!(reaction.actor.as_ref().map(|u| u.name.as_str()), Some("Phanh Blank"));
+        assert_eq!(reaction.action, "reacted to bboyviet's post in the thread");
+        assert_eq!(reaction.target, Some("Hỏi về bàn phím cơ".to_string()));
+    }
+
+    #[test]
+    fn test_profile_posts() {
+        let path = Path::new("resources/tests/profile_posts.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_activities(document.nth(0).unwrap(), 1).unwrap();
+        assert!(result.has_more);
+        assert_eq!(result.activities.len(), 2);
+        assert_eq!(result.activities[0].target, Some("cherry-picked".to_string()));
+        assert_eq!(result.activities[0].link, Some("/profile-posts/1234560/".to_string()));
+        assert_eq!(result.activities[0].snippet, "Chúc mừng năm mới fen");
+        assert_eq!(result.activities[1].action, "updated their status");
+        assert_eq!(result.activities[1].time, "Yesterday at 10:10 PM");
+        assert_eq!(parse_find_new_id("/whats-new/profile-posts/4561237/page-2"), Some("4561237".to_string()));
+        assert_eq!(parse_find_new_id("/whats-new/posts/"), None);
+    }
+
+    #[test]
+    fn test_reply_form() {
+        let path = Path::new("resources/tests/thread.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_editor_form(document.find(Class("js-quickReply")).next().unwrap()).unwrap();
+        assert_eq!(result.url, "/t/tiktoker-55-8-trieu-nguoi-theo-doi-bi-bat.896639/add-reply");
+        assert_eq!(result.token, "1703425862,cc7c9335c5b8efe00ef55af56de0e83f");
+        assert_eq!(result.attachment_hash, Some("e2689df16e6f4f590844c316c7fc5a33".to_string()));
+        assert_eq!(result.hidden_fields.get("last_date").map(String::as_str), Some("1703396822"));
+        assert_eq!(parse_post_id_from_url("https://voz.vn/t/abc.896639/post-29012345"), Some("29012345".to_string()));
+        assert_eq!(parse_post_id_from_url("/p/29012345/"), Some("29012345".to_string()));
+    }
+
+    #[test]
+    fn test_post_thread_form() {
+        let path = Path::new("resources/tests/post_thread.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let form = document.find(Name("form")).next().unwrap();
+        let prefixes = parse_thread_prefixes(form);
+        assert_eq!(prefixes.len(), 3);
+        assert_eq!(prefixes[0], ThreadPrefix { id: "4".to_string(), title: "thảo luận".to_string(), prefix_type: "royalBlue".to_string() });
+        assert_eq!(prefixes[2].prefix_type, "red");
+        let editor = parse_editor_form(form).unwrap();
+        assert_eq!(editor.url, "/f/ban-phim-chuot-gear.33/post-thread");
+        assert_eq!(editor.attachment_hash, Some("4a0e7dd54c4e2a1e24cb1c48fcb1a2a8".to_string()));
+        assert!(!editor.hidden_fields.contains_key("watch_thread"));
+    }
+
+    #[test]
+    fn test_post_edit_form() {
+        let path = Path::new("resources/tests/post_edit.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let form = document.find(Name("form")).next().unwrap();
+        let message = parse_editor_message(form).unwrap();
+        assert_eq!(message, "[QUOTE=\"Dutchman, post: 29542789, member: 1710587\"]Bắt là đúng[/QUOTE]\nĐu idol thì cũng phải có [B]giới hạn[/B] chứ nhỉ & <3");
+        assert_eq!(parse_editor_form(form).unwrap().url, "/p/29542846/edit");
+    }
+
+    #[test]
+    fn test_ignored() {
+        let path = Path::new("resources/tests/ignored.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let members = parse_ignored_members(document.find(Class("p-body")).next().unwrap()).unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].id, "999001");
+        assert_eq!(members[1].name, "spam bot");
+        let post = parse_post(document.find(Name("article")).next().unwrap()).unwrap();
+        assert!(post.is_ignored);
+        assert_eq!(post.post_id, "29543001");
+        assert_eq!(post.author_id, "999001");
+        assert_eq!(post.author_link, Some("/u/troll-vn.999001/".to_string()));
+        assert_eq!(post.created, "Today at 1:00 PM");
+        assert!(post.html_content.is_empty());
+    }
+
+    #[test]
+    fn test_poll() {
+        let path = Path::new("resources/tests/poll.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let poll = parse_poll(document.nth(0).unwrap()).unwrap();
+        assert_eq!(poll.question, "Bạn chọn layout bàn phím nào?");
+        assert_eq!(poll.options.len(), 3);
+        assert_eq!(poll.options[0].id, Some("11".to_string()));
+        assert_eq!(poll.options[0].votes, Some(120));
+        assert_eq!(poll.options[1].percentage, Some(25.0));
+        assert_eq!(poll.options[2].text, "60%");
+        assert_eq!(poll.max_votes, 0);
+        assert_eq!(poll.total_voters, Some(180));
+        assert!(poll.is_closed && poll.has_voted && !poll.can_vote);
+
+        let path = Path::new("resources/tests/poll_vote.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let poll = parse_poll(document.nth(0).unwrap()).unwrap();
+        assert_eq!(poll.options.iter().map(|o| o.text.as_str()).collect::<Vec<&str>>(), vec!["Red", "Brown", "Silent Black"]);
+        assert_eq!(poll.options[2].id, Some("9".to_string()));
+        assert_eq!(poll.max_votes, 2);
+        assert_eq!(poll.close_date, Some("Dec 31, 2023".to_string()));
+        assert_eq!(poll.close_timestamp, Some(1703955600));
+        assert!(poll.can_vote && !poll.has_voted && !poll.is_closed);
+    }
+
+    #[test]
+    fn test_post_reactions() {
+        let path = Path::new("resources/tests/reactions.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_post_reactions(document.nth(0).unwrap(), 1).unwrap();
+        assert_eq!(result.users.len(), 3);
+        assert!(result.has_more);
+        assert_eq!(result.users[0].user.id, "1234567");
+        assert_eq!(result.users[0].user.avatar, "https://data.voz.vn/avatars/s/1234/1234567.jpg?1690000000");
+        assert_eq!(result.users[0].time, "Today at 3:21 PM");
+        assert_eq!(result.users[0].timestamp, Some(1703406064));
+        assert_eq!(result.users[1].user.name, "Phanh Blank");
+        assert_eq!(result.users[1].user.avatar, "https://ui-avatars.com/api/?length=1&rounded=true&name=Phanh+Blank&background=5c85d6&color=f0f5fc");
+        assert_eq!(result.users[1].reaction.id, 2);
+        assert_eq!(result.users[1].reaction.title, "Gạch");
+    }
+
+    #[test]
+    fn test_conversations() {
+        let path = Path::new("resources/tests/conversations.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_conversations(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.current_page, "1");
+        assert_eq!(result.total_page, "2");
+        assert_eq!(result.conversations.len(), 2);
+        let first = &result.conversations[0];
+        assert_eq!(first.id, "123456");
+        assert_eq!(first.title, "Test rust parser");
+        assert_eq!(first.starter, "heroxrust");
+        assert_eq!(first.participants, vec!["heroxrust", "cherry-picked"]);
+        assert_eq!(first.replies, "3");
+        assert_eq!(first.latest, "Today at 8:01 PM");
+        assert_eq!(first.latest_author, Some("cherry-picked".to_string()));
+        assert!(first.is_unread);
+        assert!(!result.conversations[1].is_unread);
+    }
+
+    #[test]
+    fn test_conversation_detail() {
+        let path = Path::new("resources/tests/conversation.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_conversation_detail(document.nth(0).unwrap()).unwrap();
+        assert_eq!(result.title, "Test rust parser");
+        assert!(result.can_reply);
+        assert_eq!(result.participants.len(), 2);
+        assert_eq!(result.participants[1].avatar, "https://ui-avatars.com/api/?length=1&rounded=true&name=cherry-picked&background=cc3333&color=f0c2c2");
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].post_type, "convMessage");
+        assert_eq!(result.messages[0].post_id, "12357523");
+        assert!(result.messages[0].contents.iter().any(|c| matches!(c, ContentType::Embeded { site, .. } if site == "facebook")));
+        assert_eq!(parse_post_id_from_url("/conversations/messages/12357523/"), Some("12357523".to_string()));
+    }
+
+    #[test]
+    fn test_user_not_logged_in() {
+        let path = Path::new("resources/tests/login_form.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_current_user(document.nth(3).unwrap());
+        assert!(matches!(result, Err(VozError::NotLoggedIn)));
     }
 
     // #[test]
@@ -321,5 +1290,49 @@ mod tests {
         assert_eq!(result.total_page, "3");
         assert_eq!(result.posts.len(), 20);
         assert_eq!(result.can_reply, true);
+        assert!(!result.posts[0].is_bookmarked);
+        assert_eq!(result.id, "896639");
+        assert_eq!(result.title, "TikToker 55,8 triệu người theo dõi bị bắt");
+        assert!(result.prefix.is_none());
+        assert_eq!(result.starter.map(|u| (u.id, u.name, u.avatar)), Some(("1673470".to_string(), "xinata".to_string(), "https://data.voz.vn/avatars/m/1673/1673470.jpg?1600662275".to_string())));
+        assert_eq!(result.breadcrumbs, vec![
+            Breadcrumb { id: "16".to_string(), title: "Khu vui chơi giải trí".to_string(), forum_type: "c".to_string(), link: "/#khu-vui-choi-giai-tri.16".to_string() },
+            Breadcrumb { id: "33".to_string(), title: "Điểm báo".to_string(), forum_type: "f".to_string(), link: "/f/diem-bao.33/".to_string() }
+        ]);
+        assert!(!result.is_watched);
+        assert_eq!(result.posts[0].position, 1);
+        assert_eq!(result.posts[0].author_link, Some("/u/xinata.1673470/".to_string()));
+        assert_eq!(result.is_bookmarked, Some(false));
+    }
+
+    #[test]
+    fn test_thread_prefix() {
+        let path = Path::new("resources/tests/thread_prefix.html");
+        let content = fs::read_to_string(path).expect("File not found");
+        let document = Document::from_read(content.as_bytes()).expect("Invalid Html");
+
+        let result = parse_thread_detail(document.find(Class("p-body")).next().unwrap()).unwrap();
+        assert_eq!(result.id, "73313");
+        assert_eq!(result.title, "[Dịch] Hướng dẫn OC DDR4");
+        assert_eq!(result.prefix.map(|p| (p.id, p.title, p.prefix_type)), Some(("17".to_string(), "kiến thức".to_string(), "royalBlue".to_string())));
+        let starter = result.starter.unwrap();
+        assert_eq!((starter.id.as_str(), starter.name.as_str()), ("1199871", "troll159753"));
+        assert_eq!(starter.avatar, "https://data.voz.vn/avatars/m/1199/1199871.jpg?1586365876");
+        assert_eq!(result.breadcrumbs[1].link, "/f/overclocking-cooling-modding.6/");
+        assert!(result.is_watched);
+        assert_eq!(result.total_page, "53");
+        assert_eq!(result.is_bookmarked, Some(true));
+
+        // Translated phrases and a starter without a post on the page
+        let html = r#"<div class="p-body"><h1 class="p-title-value">Hỏi về bàn phím cơ</h1>
+            <div class="p-description"><a href="/u/cherry-picked.1932329/" class="username" data-user-id="1932329">cherry-picked</a>
+            <a href="/t/hoi-ve-ban-phim-co.880001/">Dec 20, 2023</a></div>
+            <a href="/t/hoi-ve-ban-phim-co.880001/watch" class="button--link button" data-sk-watch="Theo dõi"
+                data-sk-unwatch="Bỏ theo dõi"><span class="button-text">Bỏ theo dõi</span></a></div>"#;
+        let document = Document::from(html);
+        let result = parse_thread_detail(document.find(Class("p-body")).next().unwrap()).unwrap();
+        assert_eq!(result.id, "880001");
+        assert!(result.is_watched);
+        assert!(result.starter.unwrap().avatar.starts_with("https://ui-avatars.com/"));
     }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 use reqwest::Url;
 use select::{predicate::*, node::Node, document::Document};
 use models::*;
-use super::{models, parse_utils::{parse_post_contents, TrimmedString}};
+use super::{models, error::{VozError, OrParseError}, parse_utils::{parse_post_contents, TrimmedString}};
 
 enum Type {
     Quote, Code, Image, Spoiler, Embedded, Url, Table
@@ -19,7 +19,7 @@ impl Type {
             Self::Spoiler => "bbCodeSpoiler",
             Self::Embedded => "bbMediaJustifier",
             Self::Url => "bbCodeBlock--unfurl",
-            Self::Table => "table"
+            Self::Table => "bbTable"
         };
         return result.to_string()
     }
@@ -29,15 +29,16 @@ fn get_content_type(class: &str) -> Option<Type> {
     Type::all().into_iter().find(|i| class.contains(i.get_class().as_str()))
 }
 
-pub fn parse_content(node: Node) -> Result<Option<ContentType>, Box<dyn std::error::Error>> {
+pub fn parse_content(node: Node) -> Result<Option<ContentType>, VozError> {
     let class = node.attr("class").unwrap_or("");
-    let mut _type = get_content_type(class);
+    // Media sites without a justifier put the site id on the wrapper itself
+    let mut _type = get_content_type(class).or_else(|| (class.contains("bbMediaWrapper") && node.attr("data-media-site-id").is_some()).then_some(Type::Embedded));
     let mut x = node;
     if _type.is_none() {
         // Check if type is none but has child is image then add it as image
-        _type = node.find(Class("bbImageWrapper")).next().map(|_| Type::Image);
-        if _type.is_some() {
-            x = node.find(Class("bbImageWrapper")).next().unwrap();
+        if let Some(image) = sole_image(node) {
+            _type = Some(Type::Image);
+            x = image;
         }
     }
     if _type.is_none() {
@@ -65,7 +66,7 @@ pub fn parse_content(node: Node) -> Result<Option<ContentType>, Box<dyn std::err
                 Ok(result)
             },
             Type::Image => {
-                let result = x.attr("data-src").map(|s| ContentType::Image { src: parse_proxy_image(s) });
+                let result = x.attr("data-src").or(x.attr("data-url")).map(|s| ContentType::Image { src: parse_proxy_image(s) });
                 Ok(result)
             },
             Type::Spoiler => {
@@ -75,7 +76,7 @@ pub fn parse_content(node: Node) -> Result<Option<ContentType>, Box<dyn std::err
                 Ok(result)
             },
             Type::Url => {
-                let url = x.attr("data-url").map(|s| s.to_string()).ok_or("Not found url")?;
+                let url = x.attr("data-url").map(|s| s.to_string()).or_parse(".bbCodeBlock--unfurl[data-url]", "Not found url")?;
                 let host = x.attr("data-host").map(|s| s.to_string()).unwrap_or_default();
                 let thumbnail = x.find(Class("contentRow-figure").descendant(Name("img"))).next().and_then(|n| n.attr("src")).map(|s| parse_proxy_image(s));
                 let title = x.find(Class("contentRow-header")).next().map(|n| n.text().trimmed()).unwrap_or(url.clone());
@@ -86,18 +87,185 @@ pub fn parse_content(node: Node) -> Result<Option<ContentType>, Box<dyn std::err
             Type::Embedded => {
                 let site = x.attr("data-media-site-id").unwrap_or_default().to_string();
                 let title = x.attr("data-media-key").unwrap_or_default().to_string();
+                // Only some providers render a link, the site id and media key are enough for [MEDIA]
                 let link = x.find(Attr("data-href", ())).next().and_then(|n| n.attr("data-href")).map(|s| s.to_string());
-                let result = link.map(|s| ContentType::Embeded { site, title, link: s });
+                let result = Some(ContentType::Embeded { site, title, link }).filter(|_| x.attr("data-media-site-id").is_some());
                 Ok(result)
             },
             Type::Table => {
-                // x.find(Name("table")).next().map(|n| n.html().trimmed()).map(|s| ContentType::Table { content: s })
-                Ok(None)
+                x.find(Name("table")).next().map(parse_table).transpose()
             }
         }
     }
 }
 
+/// The image of a node that holds nothing else, such as a link around an image. Lists, tables and
+/// paragraphs that also have text or other elements keep their structure.
+fn sole_image(node: Node) -> Option<Node> {
+    // Linked images ([URL][IMG]) are rendered without the lightbox wrapper
+    let image = node.find(Class("bbImageWrapper")).next().or_else(|| node.find(And(Name("img"), Class("bbImage"))).next())?;
+    let is_container = node.is(Or(Name("ul"), Or(Name("ol"), Name("table"))));
+    let others = node.find(Or(Name("img"), Name("iframe"))).count() > 1 || node.find(Class("smilie")).next().is_some();
+    (!is_container && !others && node.text().trim().is_empty()).then_some(image)
+}
+
+/// Elements read as inline content, anything else that is not a known block is kept as raw html.
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "del", "dfn", "em", "font", "i", "img", "ins", "kbd", "label",
+    "mark", "q", "s", "samp", "script", "small", "span", "strike", "strong", "style", "sub", "sup", "time", "u", "var", "wbr"
+];
+
+/// Block level markup that is not a XenForo BBCode block: headings, lists, rules and wrappers such as alignment divs.
+pub fn parse_block(node: Node) -> Result<Option<Vec<ContentType>>, VozError> {
+    let name = node.name().unwrap_or_default();
+    let result = match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            // XenForo renders [HEADING=1] as h2
+            let level = name[1..].parse::<i64>().unwrap_or(2).saturating_sub(1).max(1);
+            let content = normalize_inlines(node.children().flat_map(parse_inline).collect());
+            vec![ContentType::Heading { level, content }]
+        },
+        "hr" => vec![ContentType::HorizontalRule],
+        "ul" | "ol" => {
+            let items = node.children().filter(|n| n.is(Name("li"))).map(parse_post_contents).collect::<Result<Vec<Vec<ContentType>>, VozError>>()?;
+            vec![ContentType::List { ordered: name == "ol", items }]
+        },
+        "table" => vec![parse_table(node)?],
+        "div" | "p" | "blockquote" => parse_post_contents(node)?,
+        // iframes, videos and unknown embeds are not flattened to their text
+        name if !name.is_empty() && !INLINE_ELEMENTS.contains(&name) => vec![ContentType::Html { content: node.html() }],
+        _ => return Ok(None)
+    };
+    Ok(Some(result))
+}
+
+/// The first row becomes the header when it is in a `thead` or made only of `th` cells.
+fn parse_table(node: Node) -> Result<ContentType, VozError> {
+    // html5ever wraps rows written directly in the table into a tbody
+    let rows = node.children().filter(|n| n.is(Or(Name("thead"), Or(Name("tbody"), Name("tfoot"))))).flat_map(|n| n.children())
+        .filter(|n| n.is(Name("tr")))
+        .map(|row| row.children().filter(|n| n.is(Or(Name("th"), Name("td")))).map(parse_table_cell).collect::<Result<Vec<TableCell>, VozError>>())
+        .collect::<Result<Vec<Vec<TableCell>>, VozError>>()?;
+    let has_head = node.find(Name("thead").descendant(Name("tr"))).next().is_some();
+    let mut rows = rows.into_iter().filter(|r| !r.is_empty()).collect::<Vec<Vec<TableCell>>>();
+    let header = match rows.first() {
+        Some(first) if has_head || first.iter().all(|c| c.is_header) => Some(rows.remove(0)),
+        _ => None
+    };
+    Ok(ContentType::Table { header, rows })
+}
+
+fn parse_table_cell(node: Node) -> Result<TableCell, VozError> {
+    let span = |name: &str| node.attr(name).and_then(|s| s.trim().parse::<i64>().ok()).filter(|n| *n > 0).unwrap_or(1);
+    Ok(TableCell { content: parse_post_contents(node)?, colspan: span("colspan"), rowspan: span("rowspan"), is_header: node.is(Name("th")) })
+}
+
+/// Parses the text level markup of a post. Elements without a structured form are replaced by their children.
+pub fn parse_inline(node: Node) -> Vec<Inline> {
+    if let Some(text) = node.as_text() {
+        return vec![Inline::Text { text: text.to_string() }];
+    }
+    let content = || node.children().flat_map(parse_inline).collect::<Vec<Inline>>();
+    match node.name().unwrap_or_default() {
+        "br" => vec![Inline::LineBreak],
+        "b" | "strong" => vec![Inline::Bold { content: content() }],
+        "i" | "em" => vec![Inline::Italic { content: content() }],
+        "u" | "ins" => vec![Inline::Underline { content: content() }],
+        "s" | "strike" | "del" => vec![Inline::Strike { content: content() }],
+        "code" => vec![Inline::InlineCode { code: node.text() }],
+        "span" if node.attr("style").is_some() => {
+            // Color and size can share one span, the size ends up inside the color
+            let styles = node.attr("style").unwrap_or_default().split(';').filter_map(|s| s.split_once(':')).map(|(k, v)| (k.trim(), v.trim())).collect::<Vec<(&str, &str)>>();
+            let style = |name: &str| styles.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string());
+            let mut result = content();
+            if let Some(size) = style("font-size") {
+                result = vec![Inline::Size { size, content: result }];
+            }
+            if let Some(color) = style("color") {
+                result = vec![Inline::Color { color, content: result }];
+            }
+            result
+        },
+        "img" if node.is(Class("smilie")) => {
+            let code = node.attr("data-shortname").or(node.attr("alt")).unwrap_or_default().to_string();
+            node.attr("src").map(|src| vec![Inline::Smilie { code, src: src.to_string() }]).unwrap_or_default()
+        },
+        "a" if node.attr("data-user-id").is_some() => {
+            let user_id = node.attr("data-user-id").unwrap_or_default().to_string();
+            vec![Inline::Mention { user_id, name: node.text().trim().trim_start_matches('@').to_string() }]
+        },
+        "a" => match node.attr("href") {
+            Some(url) => vec![Inline::Link { url: url.to_string(), content: content() }],
+            None => content()
+        },
+        "script" | "style" => vec![],
+        _ => content()
+    }
+}
+
+/// Collapses the whitespace of the html source like a browser would and trims every line of the paragraph.
+pub fn normalize_inlines(inlines: Vec<Inline>) -> Vec<Inline> {
+    let inlines = collapse_whitespace(inlines);
+    let is_break = |i: Option<&Inline>| i.map_or(true, |i| *i == Inline::LineBreak);
+    let trim = |c: char| c.is_whitespace() || c == '\u{200b}';
+    let mut result: Vec<Inline> = vec![];
+    for (index, inline) in inlines.iter().enumerate() {
+        match inline {
+            Inline::Text { text } => {
+                let mut text = text.as_str();
+                if is_break(result.last()) {
+                    text = text.trim_start_matches(trim);
+                }
+                if is_break(inlines.get(index + 1)) {
+                    text = text.trim_end_matches(trim);
+                }
+                if !text.is_empty() {
+                    result.push(Inline::Text { text: text.to_string() });
+                }
+            },
+            _ => result.push(inline.clone())
+        }
+    }
+    let start = result.iter().position(|i| *i != Inline::LineBreak).unwrap_or(result.len());
+    let end = result.iter().rposition(|i| *i != Inline::LineBreak).map_or(start, |i| i + 1);
+    result.drain(start..end).collect()
+}
+
+fn collapse_whitespace(inlines: Vec<Inline>) -> Vec<Inline> {
+    let mut result: Vec<Inline> = vec![];
+    for inline in inlines {
+        let inline = match inline {
+            Inline::Text { text } => {
+                let mut collapsed = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+                if text.starts_with(char::is_whitespace) {
+                    collapsed.insert(0, ' ');
+                }
+                if text.ends_with(char::is_whitespace) && collapsed != " " {
+                    collapsed.push(' ');
+                }
+                if let Some(Inline::Text { text: last }) = result.last_mut() {
+                    last.push_str(if last.ends_with(' ') { collapsed.trim_start_matches(' ') } else { &collapsed });
+                    continue;
+                }
+                if collapsed.is_empty() {
+                    continue;
+                }
+                Inline::Text { text: collapsed }
+            },
+            Inline::Bold { content } => Inline::Bold { content: collapse_whitespace(content) },
+            Inline::Italic { content } => Inline::Italic { content: collapse_whitespace(content) },
+            Inline::Underline { content } => Inline::Underline { content: collapse_whitespace(content) },
+            Inline::Strike { content } => Inline::Strike { content: collapse_whitespace(content) },
+            Inline::Color { color, content } => Inline::Color { color, content: collapse_whitespace(content) },
+            Inline::Size { size, content } => Inline::Size { size, content: collapse_whitespace(content) },
+            Inline::Link { url, content } => Inline::Link { url, content: collapse_whitespace(content) },
+            other => other
+        };
+        result.push(inline);
+    }
+    result
+}
+
 fn parse_proxy_image(uri: &str) -> String {
     let mut url_str = uri.to_string();
     if uri.starts_with("/proxy") {
@@ -129,13 +297,13 @@ pub fn parse_reactions(node: Node) -> Option<ReactionSummary> {
     Some(ReactionSummary { icons, message: message.unwrap().text() })
 }
 
-pub fn parse_list_reactions(content: String) -> Result<Vec<Reaction>, Box<dyn std::error::Error>> {
+pub fn parse_list_reactions(content: String) -> Result<Vec<Reaction>, VozError> {
     let mut reactions: Vec<Reaction> = vec![];
-    let document = Document::from_read(content.as_bytes())?;
+    let document = Document::from(content.as_str());
     let nodes = document.find(Class("reaction"));
     for node in nodes {
-        let id = node.attr("data-reaction-id").unwrap_or("0").to_string().parse::<i64>()?;
-        let child = node.find(Name("img")).next().ok_or("Not found reaction info")?;
+        let id = node.attr("data-reaction-id").unwrap_or("0").parse::<i64>().ok().or_parse(".reaction[data-reaction-id]", "Invalid reaction id")?;
+        let child = node.find(Name("img")).next().or_parse(".reaction img", "Not found reaction info")?;
         let icon = child.attr("src").unwrap_or("unknown.png").to_string();
         let title = child.attr("title").unwrap_or("title").to_string();
 
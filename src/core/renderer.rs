@@ -0,0 +1,185 @@
+use reqwest::Url;
+
+use super::models::{ContentType, Inline, TableCell};
+
+/// Renders a content tree to html shaped like XenForo's `bbWrapper` output, so `parse_post_contents` reads it back.
+/// Text and attributes are escaped, urls other than http(s), mailto and site relative ones are dropped,
+/// and colors or sizes that are not plain css values are ignored. `ContentType::Html` is escaped as text too,
+/// so raw markup comes back as a paragraph of its source rather than as `Html`.
+pub fn render_html(contents: &[ContentType]) -> String {
+    contents.iter().map(render_block).collect::<Vec<String>>().join("\n")
+}
+
+fn render_block(content: &ContentType) -> String {
+    match content {
+        ContentType::Html { content } => escape(content),
+        ContentType::Paragraph { content } => render_inlines(content),
+        ContentType::Heading { level, content } => {
+            let tag = format!("h{}", (*level).clamp(1, 3) + 1);
+            format!("<{tag}>{}</{tag}>", render_inlines(content))
+        },
+        ContentType::List { ordered, items } => {
+            let tag = if *ordered { "ol" } else { "ul" };
+            let items = items.iter().map(|i| format!("<li data-xf-list-type=\"{tag}\">{}</li>", render_html(i))).collect::<Vec<String>>().join("\n");
+            format!("<{tag}>\n{items}\n</{tag}>")
+        },
+        ContentType::HorizontalRule => "<hr>".to_string(),
+        ContentType::Image { src } => match safe_url(src) {
+            Some(src) => format!("<div class=\"bbImageWrapper  js-lbImage\" title=\"\" data-src=\"{src}\"><img src=\"{src}\" data-url=\"{src}\" class=\"bbImage\" alt=\"\" loading=\"lazy\"></div>"),
+            None => String::new()
+        },
+        ContentType::QuoteBlock { author_id, author_name, post_id, content } => {
+            let attribute = |prefix: &str, value: &Option<String>| value.as_ref().map(|v| escape(&format!("{prefix}{v}"))).unwrap_or_default();
+            let title = author_name.as_ref().map(|n| format!("<div class=\"bbCodeBlock-title\">{} said:</div>", escape(n))).unwrap_or_default();
+            format!(
+                "<blockquote data-attributes=\"{}\" data-quote=\"{}\" data-source=\"{}\" class=\"bbCodeBlock bbCodeBlock--expandable bbCodeBlock--quote js-expandWatch\">{title}<div class=\"bbCodeBlock-content\"><div class=\"bbCodeBlock-expandContent js-expandContent \">{}</div></div></blockquote>",
+                attribute("member: ", author_id), attribute("", author_name), attribute("post: ", post_id), render_html(content)
+            )
+        },
+        ContentType::CodeBlock { language, content } => {
+            let title = match language.chars().next() {
+                Some(first) => format!("{}{}:", first.to_uppercase(), &language[first.len_utf8()..]),
+                None => "Code:".to_string()
+            };
+            format!(
+                "<div class=\"bbCodeBlock bbCodeBlock--screenLimited bbCodeBlock--code\"><div class=\"bbCodeBlock-title\">{}</div><div class=\"bbCodeBlock-content\" dir=\"ltr\"><pre class=\"bbCodeCode\" dir=\"ltr\" data-xf-init=\"code-block\" data-lang=\"{}\"><code>{}</code></pre></div></div>",
+                escape(&title), escape(language), escape(content)
+            )
+        },
+        ContentType::UrlBlock { thumbnail, title, content, host, url } => {
+            let Some(url) = safe_url(url) else { return escape(title) };
+            let figure = thumbnail.as_deref().and_then(safe_url).map(|src| format!("<div class=\"contentRow-figure contentRow-figure--fixedSmall js-unfurl-figure\"><img src=\"{src}\" alt=\"{}\"></div>", escape(host))).unwrap_or_default();
+            format!(
+                "<div class=\"bbCodeBlock bbCodeBlock--unfurl js-unfurl fauxBlockLink\" data-unfurl=\"true\" data-url=\"{url}\" data-host=\"{host}\"><div class=\"contentRow\">{figure}<div class=\"contentRow-main\"><h3 class=\"contentRow-header js-unfurl-title\"><a href=\"{url}\" class=\"link link--external fauxBlockLink-blockLink\" target=\"_blank\" rel=\"nofollow ugc noopener\">{}</a></h3><div class=\"contentRow-snippet js-unfurl-desc\">{}</div><div class=\"contentRow-minor contentRow-minor--hideLinks\">{host}</div></div></div></div>",
+                escape(title), escape(content), host = escape(host)
+            )
+        },
+        ContentType::Spoiler { title, content } => {
+            let title = if title.is_empty() { "Spoiler".to_string() } else { format!("Spoiler: <span class=\"bbCodeSpoiler-button-title\">{}</span>", escape(title)) };
+            format!(
+                "<div class=\"bbCodeSpoiler\"><button type=\"button\" class=\"bbCodeSpoiler-button button--longText button\" data-xf-click=\"toggle\" data-xf-init=\"tooltip\" title=\"Click to reveal or hide spoiler\"><span class=\"button-text\"><span>{title}</span></span></button><div class=\"bbCodeSpoiler-content\"><div class=\"bbCodeBlock bbCodeBlock--spoiler\"><div class=\"bbCodeBlock-content\">{}</div></div></div></div>",
+                render_html(content)
+            )
+        },
+        // Embeds are left to the client, XenForo's iframes are not reproduced
+        ContentType::Embeded { site, title, link } => {
+            let link = link.as_deref().and_then(safe_url).map(|l| format!(" data-href=\"{l}\"")).unwrap_or_default();
+            format!("<div class=\"bbMediaJustifier\" data-media-site-id=\"{}\" data-media-key=\"{}\"><div class=\"bbMediaWrapper\"{link}></div></div>", escape(site), escape(title))
+        },
+        ContentType::Table { header, rows } => {
+            let rows = header.iter().chain(rows.iter()).map(|r| format!("<tr>{}</tr>", r.iter().map(render_cell).collect::<String>())).collect::<Vec<String>>().join("\n");
+            format!("<div class=\"bbTable\"><table style=\"width: 100%\">\n{rows}\n</table></div>")
+        }
+    }
+}
+
+fn render_cell(cell: &TableCell) -> String {
+    let tag = if cell.is_header { "th" } else { "td" };
+    let colspan = if cell.colspan > 1 { format!(" colspan=\"{}\"", cell.colspan) } else { String::new() };
+    let rowspan = if cell.rowspan > 1 { format!(" rowspan=\"{}\"", cell.rowspan) } else { String::new() };
+    format!("<{tag}{colspan}{rowspan}>{}</{tag}>", render_html(&cell.content))
+}
+
+fn render_inlines(inlines: &[Inline]) -> String {
+    inlines.iter().map(|inline| match inline {
+        Inline::Text { text } => escape(text),
+        Inline::Bold { content } => format!("<b>{}</b>", render_inlines(content)),
+        Inline::Italic { content } => format!("<i>{}</i>", render_inlines(content)),
+        Inline::Underline { content } => format!("<u>{}</u>", render_inlines(content)),
+        Inline::Strike { content } => format!("<s>{}</s>", render_inlines(content)),
+        Inline::Color { color, content } if is_safe_color(color) => format!("<span style=\"color: {color}\">{}</span>", render_inlines(content)),
+        Inline::Size { size, content } if is_safe_size(size) => format!("<span style=\"font-size: {size}\">{}</span>", render_inlines(content)),
+        Inline::Color { content, .. } | Inline::Size { content, .. } => render_inlines(content),
+        Inline::Link { url, content } => match safe_url(url) {
+            Some(href) if url.starts_with('/') || Url::parse(url).ok().and_then(|u| u.host_str().map(|h| h == "voz.vn" || h.ends_with(".voz.vn"))).unwrap_or(false) => {
+                format!("<a href=\"{href}\" class=\"link link--internal\">{}</a>", render_inlines(content))
+            },
+            Some(href) => format!("<a href=\"{href}\" target=\"_blank\" class=\"link link--external\" rel=\"nofollow ugc noopener\">{}</a>", render_inlines(content)),
+            None => render_inlines(content)
+        },
+        Inline::Mention { user_id, name } => {
+            let user_id = escape(user_id);
+            format!("<a href=\"/u/{user_id}/\" class=\"username\" data-xf-init=\"member-tooltip\" data-user-id=\"{user_id}\" data-username=\"@{name}\">@{name}</a>", name = escape(name))
+        },
+        Inline::Smilie { code, src } => match safe_url(src) {
+            Some(src) => format!("<img src=\"{src}\" class=\"smilie\" loading=\"lazy\" alt=\"{code}\" title=\"{code}\" data-shortname=\"{code}\">", code = escape(code)),
+            None => escape(code)
+        },
+        Inline::InlineCode { code } => format!("<code class=\"bbCodeInlineCode\">{}</code>", escape(code)),
+        Inline::LineBreak => "<br>\n".to_string()
+    }).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#039;")
+}
+
+/// Escaped url when it is safe to put in a link or image, `None` for `javascript:` and other schemes.
+fn safe_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let allowed = (url.starts_with('/') && !url.starts_with("//")) || Url::parse(url).is_ok_and(|u| ["http", "https", "mailto"].contains(&u.scheme()));
+    allowed.then(|| escape(url))
+}
+
+fn is_safe_color(color: &str) -> bool {
+    let color = color.trim();
+    match color.strip_prefix('#') {
+        Some(hex) => [3, 6].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None if color.starts_with("rgb(") || color.starts_with("rgba(") => color.ends_with(')') && color[color.find('(').unwrap_or_default() + 1..color.len() - 1].chars().all(|c| c.is_ascii_digit() || ", .%".contains(c)),
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic())
+    }
+}
+
+fn is_safe_size(size: &str) -> bool {
+    size.strip_suffix("px").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use select::{document::Document, predicate::Class};
+
+    use super::*;
+    use crate::core::{bbcode::{html_to_bbcode, to_bbcode}, bbcode_parser::parse_bbcode, parse_utils::parse_post_contents};
+
+    #[test]
+    fn test_render_round_trip() {
+        for fixture in ["resources/tests/post.html", "resources/tests/inline.html", "resources/tests/table.html"] {
+            let content = fs::read_to_string(Path::new(fixture)).expect("File not found");
+            let bbcode = html_to_bbcode(&content).unwrap();
+            let html = format!("<div class=\"bbWrapper\">{}</div>", render_html(&parse_bbcode(&bbcode).contents));
+            let document = Document::from(html.as_str());
+            let contents = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+            assert_eq!(to_bbcode(&contents), bbcode, "{fixture}");
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let result = parse_bbcode("[URL=javascript:alert(1)]click[/URL] [IMG]javascript:alert(2)[/IMG] <script>alert(3)</script> [COLOR=red;background:url(x)]red[/COLOR] [SIZE=4]ok[/SIZE] [URL=https://example.com/a\"b]x[/URL]");
+        let html = render_html(&result.contents);
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(3)&lt;/script&gt;"));
+        assert!(!html.contains("background"));
+        assert!(html.contains("<span style=\"font-size: 15px\">ok</span>"));
+        assert!(html.contains("href=\"https://example.com/a&quot;b\""));
+
+        let result = parse_bbcode("[URL=https://voz.vn/f/diem-bao.33/]a[/URL] [URL=https://data.voz.vn/a.png]b[/URL] [URL=https://evilvoz.vn/]c[/URL]");
+        let html = render_html(&result.contents);
+        assert!(html.contains("<a href=\"https://voz.vn/f/diem-bao.33/\" class=\"link link--internal\">a</a>"));
+        assert!(html.contains("<a href=\"https://data.voz.vn/a.png\" class=\"link link--internal\">b</a>"));
+        assert!(html.contains("<a href=\"https://evilvoz.vn/\" target=\"_blank\" class=\"link link--external\""));
+    }
+
+    #[test]
+    fn test_render_raw_html() {
+        let html = render_html(&[ContentType::Html { content: "<iframe src=\"https://example.com\"></iframe>".to_string() }]);
+        assert_eq!(html, "&lt;iframe src=&quot;https://example.com&quot;&gt;&lt;/iframe&gt;");
+
+        let document = Document::from(format!("<div class=\"bbWrapper\">{html}</div>").as_str());
+        let contents = parse_post_contents(document.find(Class("bbWrapper")).next().unwrap()).unwrap();
+        assert!(matches!(&contents[..], [ContentType::Paragraph { content }] if content == &vec![Inline::Text { text: "<iframe src=\"https://example.com\"></iframe>".to_string() }]));
+    }
+}
@@ -1,5 +1,4 @@
 
-use nom::AsBytes;
 use reqwest::header::{HeaderMap, HOST};
 use reqwest::{Client, Url, Request, Response};
 use reqwest_cookie_store::{CookieStoreMutex, RawCookie, CookieStore};
@@ -8,12 +7,15 @@ use select::document::Document;
 use select::predicate::Name;
 use task_local_extensions::Extensions;
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
+use std::io;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 
+use super::storage::{CookieStorage, PERSISTED_COOKIES};
+
 /// `Session` is a user-friendly `Client` wrapper, which automatically handles cookies and load/store
-/// cookies from/to the specified path.
+/// them through an optional `CookieStorage`.
 #[derive(Debug, Clone)]
 pub struct Session {
     #[allow(dead_code)] // just make clippy happy
@@ -30,25 +32,34 @@ impl Middleware for State {
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> reqwest_middleware::Result<Response> {
-        let mut res = next.run(req, extensions).await?;
-        let content = res.chunk().await?.ok_or(reqwest_middleware::Error::Middleware(anyhow::anyhow!("")))?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or(reqwest_middleware::Error::Middleware(anyhow::anyhow!("")))?;
-        let csrf = document.find(Name("html")).next().and_then(|n| n.attr("data-csrf")).map(str::to_string);
-        *self.csrf.lock().unwrap() = csrf;
+        let res = next.run(req, extensions).await?;
+        self.persist();
         reqwest_middleware::Result::Ok(res)
     }
 }
 
 impl Session {
-    /// Try to creates a new `Session` instance, and load cookies from `cookie_store_path`.
-    /// When `Session` is dropped(more specifically, when `State` is dropped), it will store cookies
-    /// to `cookie_store_path`.
+    /// Creates a new `Session` instance without cookie persistence.
     pub fn new(base_url: String) -> Session {
-        let state_raw = State::new();
+        Self::build(base_url, State::new(None))
+    }
+
+    /// Creates a new `Session` instance and loads cookies from `storage`.
+    /// Cookies are stored back whenever a response changes them, and when `Session` is
+    /// dropped(more specifically, when `State` is dropped).
+    pub fn with_storage(base_url: String, storage: Arc<dyn CookieStorage>) -> io::Result<Session> {
+        let cookies = storage.load()?;
+        let session = Self::build(base_url, State::new(Some(storage)));
+        cookies.into_iter().for_each(|(k, v)| session.insert_cookie(k, v));
+        *session.state.saved.lock().unwrap() = session.state.persisted_cookies();
+        Ok(session)
+    }
+
+    fn build(base_url: String, state_raw: State) -> Session {
         let state = Arc::new(state_raw);
 
         let mut headers = HeaderMap::new();
-        headers.append(HOST, base_url.parse().unwrap());
+        headers.append(HOST, base_url.rsplit("://").next().unwrap_or_default().parse().unwrap());
 
         let client_raw = Client::builder()
             .user_agent("vozForums/366 CFNetwork/1331.0.7 Darwin/21.4.0")
@@ -62,11 +73,30 @@ impl Session {
     }
 
     pub fn post<U>(&self, path: U) -> RequestBuilder where U: Display {
-        self.client.post(format!("https://{0}{path}", self.base_url))
+        self.client.post(self.url(path))
     }
 
     pub fn get<U>(&self, path: U) -> RequestBuilder where U: Display {
-        self.client.get(format!("https://{0}{path}", self.base_url))
+        self.client.get(self.url(path))
+    }
+
+    /// Resolves a site relative path, absolute links returned by XenForo are kept as is.
+    fn url<U>(&self, path: U) -> String where U: Display {
+        let path = path.to_string();
+        if path.starts_with("http://") || path.starts_with("https://") {
+            path
+        } else {
+            format!("{0}{path}", self.origin())
+        }
+    }
+
+    /// `base_url` is a bare host served over https unless it spells out its scheme (e.g. a local test server).
+    fn origin(&self) -> String {
+        if self.base_url.contains("://") {
+            self.base_url.clone()
+        } else {
+            format!("https://{0}", self.base_url)
+        }
     }
 
     pub fn get_cookies(&self) -> HashMap<String, String> {
@@ -76,8 +106,13 @@ impl Session {
     }
 
     pub fn set_cookie(&self, key: String, value: String) {
+        self.insert_cookie(key, value);
+        self.state.persist();
+    }
+
+    fn insert_cookie(&self, key: String, value: String) {
         let cookie = RawCookie::build(key, value).finish();
-        let url = format!("https://{0}", self.base_url).parse::<Url>().unwrap();
+        let url = self.origin().parse::<Url>().unwrap();
         self.state.cookie_store.lock().unwrap().insert_raw(&cookie, &url).ok();
     }
 
@@ -85,6 +120,16 @@ impl Session {
         let result = self.state.csrf.lock().unwrap();
         result.clone()
     }
+
+    /// Captures the CSRF token from the `data-csrf` attribute of a full page. Fragments and json
+    /// bodies don't carry it, so the previous token is kept for them. Pages call this once parsed,
+    /// reading the body in the middleware would take it away from the caller.
+    pub fn update_csrf(&self, document: &Document) {
+        let csrf = document.find(Name("html")).next().and_then(|n| n.attr("data-csrf")).map(str::to_string);
+        if csrf.is_some() {
+            *self.state.csrf.lock().unwrap() = csrf;
+        }
+    }
 }
 
 impl Deref for Session {
@@ -94,26 +139,85 @@ impl Deref for Session {
     }
 }
 
-#[derive(Debug)]
 struct State {
     cookie_store: Arc<CookieStoreMutex>,
-    csrf: Arc<Mutex<Option<String>>>
+    csrf: Arc<Mutex<Option<String>>>,
+    storage: Option<Arc<dyn CookieStorage>>,
+    saved: Mutex<HashMap<String, String>>
 }
 
 impl State {
-    pub fn new() -> State {
+    pub fn new(storage: Option<Arc<dyn CookieStorage>>) -> State {
         let cookie_store = CookieStore::default();
         let cookie_store = Arc::new(CookieStoreMutex::new(cookie_store));
         let csrf = Arc::new(Mutex::<Option<String>>::new(None));
         State {
             cookie_store,
-            csrf: csrf
+            csrf: csrf,
+            storage,
+            saved: Mutex::new(HashMap::new())
+        }
+    }
+
+    fn persisted_cookies(&self) -> HashMap<String, String> {
+        let binding = self.cookie_store.lock().unwrap();
+        let result = binding.iter_any().map(|x| x.name_value()).filter(|(k, _)| PERSISTED_COOKIES.contains(k)).map(|(k,v)| (k.to_string(), v.to_string()));
+        HashMap::from_iter(result)
+    }
+
+    /// Stores the login cookies if they changed since the last successful save.
+    fn persist(&self) {
+        if let Some(storage) = &self.storage {
+            let cookies = self.persisted_cookies();
+            let mut saved = self.saved.lock().unwrap();
+            if *saved != cookies && storage.save(&cookies).is_ok() {
+                *saved = cookies;
+            }
         }
     }
 }
 
+impl Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State")
+            .field("cookie_store", &self.cookie_store)
+            .field("csrf", &self.csrf)
+            .field("has_storage", &self.storage.is_some())
+            .finish()
+    }
+}
+
 impl Drop for State {
     fn drop(&mut self) {
-        
+        if let Some(storage) = &self.storage {
+            storage.save(&self.persisted_cookies()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::storage::JsonFileStorage;
+
+    #[test]
+    fn test_cookies_survive_restart() {
+        let path = std::env::temp_dir().join(format!("vozclient-session-{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let storage = Arc::new(JsonFileStorage::new(&path));
+
+        let session = Session::with_storage("voz.vn".to_string(), storage.clone()).unwrap();
+        session.set_cookie("xf_user".to_string(), "1948176%2Cabc".to_string());
+        session.set_cookie("xf_session".to_string(), "session".to_string());
+        session.set_cookie("not_persisted".to_string(), "value".to_string());
+        assert_eq!(storage.load().unwrap().len(), 2);
+        drop(session);
+
+        let session = Session::with_storage("voz.vn".to_string(), storage).unwrap();
+        let cookies = session.get_cookies();
+        assert_eq!(cookies.get("xf_user").map(String::as_str), Some("1948176%2Cabc"));
+        assert_eq!(cookies.get("xf_session").map(String::as_str), Some("session"));
+        assert!(!cookies.contains_key("not_persisted"));
+        std::fs::remove_file(path).ok();
     }
 }
\ No newline at end of file
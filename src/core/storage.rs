@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+/// Cookies that keep a XenForo login alive across restarts.
+pub const PERSISTED_COOKIES: [&str; 4] = ["xf_user", "xf_session", "xf_tfa_trust", "xf_csrf"];
+
+/// Backend used by `Session` to load cookies on creation and store them whenever they change.
+pub trait CookieStorage: Send + Sync {
+    fn load(&self) -> io::Result<HashMap<String, String>>;
+    fn save(&self, cookies: &HashMap<String, String>) -> io::Result<()>;
+}
+
+/// Stores cookies as a flat JSON object. Writes go to a sibling temp file which is then renamed
+/// over the target, so a crash never leaves a half written jar behind.
+#[derive(Debug, Clone)]
+pub struct JsonFileStorage {
+    path: PathBuf
+}
+
+impl JsonFileStorage {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CookieStorage for JsonFileStorage {
+    fn load(&self) -> io::Result<HashMap<String, String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| io::Error::new(ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e)
+        }
+    }
+
+    fn save(&self, cookies: &HashMap<String, String>) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(cookies).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let path = std::env::temp_dir().join(format!("vozclient-storage-{}.json", std::process::id()));
+        let storage = JsonFileStorage::new(&path);
+        assert!(storage.load().unwrap().is_empty());
+
+        let cookies = HashMap::from([
+            ("xf_user".to_string(), "1948176%2Cabc".to_string()),
+            ("xf_session".to_string(), "session".to_string())
+        ]);
+        storage.save(&cookies).unwrap();
+        assert_eq!(storage.load().unwrap(), cookies);
+        fs::remove_file(path).ok();
+    }
+}
@@ -1,21 +1,25 @@
-use std::{fmt::Debug, collections::HashMap};
-use parse_utils::{parse_catagories, parse_forum, parse_login_form, parse_current_user, parse_thread_detail};
-use select::{document::Document, predicate::Class};
+use std::{fmt::Debug, collections::HashMap, io, path::PathBuf, sync::Arc};
+use post_parse_utils::parse_reactions;
+use parse_utils::{parse_catagories, parse_forum, parse_login_form, parse_current_user, parse_thread_detail, parse_error_message, parse_editor_form, parse_post, parse_post_id_from_url, parse_post_reactions, parse_conversations, parse_conversation_detail, parse_content_id, parse_alerts, parse_search_results, parse_search_id, parse_thread_list, parse_forum_item, parse_bookmarks, parse_activities, parse_find_new_id, parse_member_profile, parse_member_about, parse_thread_prefixes, parse_editor_message, parse_ignored_members, parse_poll};
+use reqwest::{header::RETRY_AFTER, multipart::{Form, Part}, StatusCode, Url};
+use reqwest_middleware::RequestBuilder;
+use select::{document::Document, node::Node, predicate::{And, Class, Name, Or, Predicate}};
 use serde::Serialize;
 use session::Session;
 use models::*;
+use error::{VozError, OrParseError};
+use storage::{CookieStorage, JsonFileStorage};
 
-use super::{models, session, parse_utils};
+use super::{models, session, parse_utils, post_parse_utils, error, storage};
 pub trait VozResponseMapping<T: Serialize> {
     fn voz_response(self) -> VozResponse<T>;
 }
 
-impl<T: Serialize + Debug> VozResponseMapping<T> for Result<T, Box<dyn std::error::Error>> {
+impl<T: Serialize + Debug> VozResponseMapping<T> for Result<T, VozError> {
     fn voz_response(self) -> VozResponse<T> {
-        if self.is_ok() {
-            VozResponse::Success { data: self.unwrap() }
-        } else {
-            VozResponse::Failed { message: self.unwrap_err().to_string() }
+        match self {
+            Ok(data) => VozResponse::Success { data },
+            Err(e) => VozResponse::Failed { code: e.code().to_string(), message: e.to_string() }
         }
     }
 }
@@ -30,9 +34,73 @@ impl VozCore {
             client: Session::new(base_url)
         }
     }
+
+    /// Creates a client whose login cookies are loaded from and saved to `storage`.
+    pub fn with_storage(base_url: String, storage: Arc<dyn CookieStorage>) -> io::Result<Self> {
+        Ok(Self {
+            client: Session::with_storage(base_url, storage)?
+        })
+    }
+
+    /// Shorthand for `with_storage` backed by a JSON file at `path`.
+    pub fn with_cookie_file<P: Into<PathBuf>>(base_url: String, path: P) -> io::Result<Self> {
+        Self::with_storage(base_url, Arc::new(JsonFileStorage::new(path)))
+    }
 }
 
 impl VozCore {
+    /// Sends the request and parses the body, mapping XenForo error pages and bad statuses to `VozError`.
+    async fn fetch(&self, request: RequestBuilder) -> Result<Document, VozError> {
+        self.fetch_page(request).await.map(|(document, _)| document)
+    }
+
+    /// Same as `fetch`, also returning the url the page was served from once redirects are followed.
+    async fn fetch_page(&self, request: RequestBuilder) -> Result<(Document, Url), VozError> {
+        let response = request.send().await?;
+        let url = response.url().clone();
+        let status = response.status();
+        let retry_after = response.headers().get(RETRY_AFTER).and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<u64>().ok());
+        let content = response.text().await?;
+        let document = Document::from(content.as_str());
+        if status.is_success() {
+            self.client.update_csrf(&document);
+            return Ok((document, url));
+        }
+        let message = document.nth(0).and_then(parse_error_message);
+        let is_login_page = document.find(Name("form")).any(|n| n.attr("action").is_some_and(|a| a.contains("/login/login")));
+        Err(classify_error(status, message, is_login_page, retry_after))
+    }
+
+    /// Sends the request as a XenForo ajax call and decodes the json body, turning `"status": "error"` into `VozError`.
+    async fn fetch_json(&self, request: RequestBuilder) -> Result<XfJsonResponse, VozError> {
+        let response = request.query(&[("_xfResponseType", "json"), ("_xfWithData", "1")]).header("X-Requested-With", "XMLHttpRequest").send().await?;
+        let status = response.status();
+        let retry_after = response.headers().get(RETRY_AFTER).and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<u64>().ok());
+        let content = response.text().await?;
+        match serde_json::from_str::<XfJsonResponse>(&content) {
+            Ok(json) if json.status.as_deref() == Some("error") || !status.is_success() => {
+                let message = Some(json.error_messages().join("\n")).filter(|s| !s.is_empty());
+                Err(classify_error(status, message, false, retry_after))
+            },
+            Ok(json) => Ok(json),
+            Err(_) if status.is_success() => Err(VozError::parse("json", "Response is not a XenForo json response")),
+            Err(_) => {
+                let document = Document::from(content.as_str());
+                let message = document.nth(0).and_then(parse_error_message);
+                Err(classify_error(status, message, false, retry_after))
+            }
+        }
+    }
+
+    /// CSRF token for ajax actions, loading the home page first when no page has been fetched yet.
+    async fn csrf(&self) -> Result<String, VozError> {
+        if let Some(token) = self.client.get_csrf() {
+            return Ok(token);
+        }
+        self.fetch(self.client.get("/")).await?;
+        self.client.get_csrf().ok_or(VozError::NotLoggedIn)
+    }
+
     pub fn set_user(&self, user: String, session: String, tfa: Option<String>) {
         self.client.set_cookie("xf_user".to_string(), user);
         self.client.set_cookie("xf_session".to_string(), session);
@@ -41,25 +109,22 @@ impl VozCore {
         }
     }
     
-    pub async fn get_categories(&self) -> Result<Vec<Category>, Box<dyn std::error::Error>> {
-        let content = self.client.get("/").send().await?.text().await?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
+    pub async fn get_categories(&self) -> Result<Vec<Category>, VozError> {
+        let document = self.fetch(self.client.get("/")).await?;
         let results = document.find(Class("block--category")).filter_map(|x| parse_catagories(x).ok()).collect::<Vec<Category>>();
         Ok(results)
     }
 
-    pub async fn get_forum(&self, id: String, forum_type: String, page: i64) -> Result<Forum, Box<dyn std::error::Error>> {
-        let content = self.client.get(format!("/{forum_type}/{id}/page-{page}")).send().await?.text().await?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
-        let node = document.find(Class("p-body")).next().ok_or("p-body does not exist")?;
+    pub async fn get_forum(&self, id: String, forum_type: String, page: i64) -> Result<Forum, VozError> {
+        let document = self.fetch(self.client.get(format!("/{forum_type}/{id}/page-{page}"))).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
         let result = parse_forum(node)?;
         Ok(result)
     }
 
-    pub async fn login(&self, username: String, password: String) -> Result<LoginResult, Box<dyn std::error::Error>> {
-        let content = self.client.get("/login/login").send().await?.text().await?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
-        let node = document.find(Class("p-body")).next().ok_or("p-body does not exist")?;
+    pub async fn login(&self, username: String, password: String) -> Result<LoginResult, VozError> {
+        let document = self.fetch(self.client.get("/login/login")).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
         let login_info = parse_login_form(node)?;
         let form = HashMap::from([
             ("_xfToken", login_info.token),
@@ -67,26 +132,29 @@ impl VozCore {
             ("password", password),
             ("remember", "1".to_string()),
         ]);
-        let content = self.client.post(login_info.url).form(&form).send().await?.text().await?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
+        let document = match self.fetch(self.client.post(login_info.url).form(&form)).await {
+            Err(VozError::XenForo { message }) => return Err(VozError::InvalidCredentials { message }),
+            result => result?
+        };
         let cookies = self.client.get_cookies();
         if cookies.contains_key("xf_session") {
             if cookies.contains_key("xf_user") {
-                let node = document.find(Class("p-nav")).next().ok_or("p-nav does not exist")?;
+                let node = document.find(Class("p-nav")).next().or_parse(".p-nav", "p-nav does not exist")?;
                 let user_info = parse_current_user(node)?;
                 Ok(LoginResult::Success { user: cookies.get("xf_user").unwrap().to_string(), session: cookies.get("xf_session").unwrap().to_string(), tfa_trust: None, info: user_info})
             } else {
-                let node = document.find(Class("p-body")).next().ok_or("p-body does not exist")?;
+                let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
                 let login_info = parse_login_form(node)?;
                 Ok(LoginResult::MFA { url: login_info.url })
             }
         } else {
-            Err("Incorrect login information. Please try again".into())
+            let message = document.nth(0).and_then(parse_error_message).unwrap_or("Incorrect login information. Please try again".to_string());
+            Err(VozError::InvalidCredentials { message })
         }
         
     }
 
-    pub async fn mfa(&self, url: String, code: String, provider: String) -> Result<LoginResult, Box<dyn std::error::Error>> {
+    pub async fn mfa(&self, url: String, code: String, provider: String) -> Result<LoginResult, VozError> {
         let form: HashMap<&str, _> = HashMap::from([
             ("_xfToken", self.client.get_csrf().unwrap_or_default()),
             ("trust", 1.to_string()),
@@ -95,42 +163,582 @@ impl VozCore {
             ("code", code),
             ("provider", provider)
         ]);
-        let content = self.client.post(url).form(&form).send().await?.text().await?;
+        let document = match self.fetch(self.client.post(url).form(&form)).await {
+            Err(VozError::XenForo { message }) => return Err(VozError::InvalidCredentials { message }),
+            result => result?
+        };
         let cookies = self.client.get_cookies();
         if cookies.contains_key("xf_user") {
-            let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
-            let node = document.find(Class("p-nav")).next().ok_or("p-nav does not exist")?;
+            let node = document.find(Class("p-nav")).next().or_parse(".p-nav", "p-nav does not exist")?;
             let user_info = parse_current_user(node)?;
             Ok(LoginResult::Success { user: cookies.get("xf_user").unwrap().to_string(), session: cookies.get("xf_session").unwrap().to_string(), tfa_trust: cookies.get("xf_tfa_trust").map(|a| a.to_string()), info: user_info})
         } else {
-            Err("Incorrect login information. Please try again".into())
+            let message = document.nth(0).and_then(parse_error_message).unwrap_or("Incorrect login information. Please try again".to_string());
+            Err(VozError::InvalidCredentials { message })
         }
     } 
 
-    pub async fn get_current_user(&self) -> Result<User, Box<dyn std::error::Error>> {
-        let content = self.client.get("/").send().await?.text().await?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
-        let node = document.find(Class("p-nav")).next().ok_or("p-nav does not exist")?;
+    pub async fn get_current_user(&self) -> Result<CurrentUser, VozError> {
+        let document = self.fetch(self.client.get("/")).await?;
+        let node = document.find(Class("p-nav")).next().or_parse(".p-nav", "p-nav does not exist")?;
         let user_info = parse_current_user(node)?;
         Ok(user_info)
     }
 
-    pub async fn get_thread(&self, id: String, page: Option<i64>) -> Result<Thread, Box<dyn std::error::Error>> {
+    /// Loads a member profile with its About tab and latest activity. `id` is a user id such as `Post::author_id`.
+    pub async fn get_member(&self, id: String) -> Result<MemberProfile, VozError> {
+        let document = self.fetch(self.client.get(format!("/u/{id}/"))).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        let mut profile = parse_member_profile(node)?;
+        if let Some(document) = hidden_tab(self.fetch(self.client.get(format!("/u/{id}/about"))).await)? {
+            if let Some(node) = document.nth(0) {
+                (profile.about, profile.custom_fields) = parse_member_about(node);
+            }
+        }
+        if let Some(document) = hidden_tab(self.fetch(self.client.get(format!("/u/{id}/latest-activity"))).await)? {
+            if let Some(activities) = document.nth(0).and_then(|n| parse_activities(n, 1).ok()) {
+                profile.recent_activity = activities.activities;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Reports the post to the moderators with `reason` as the message.
+    pub async fn report_post(&self, post_id: String, reason: String) -> Result<(), VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?), ("message", reason)]);
+        self.fetch_json(self.client.post(format!("/p/{post_id}/report")).form(&form)).await?;
+        Ok(())
+    }
+
+    pub async fn get_ignored_members(&self) -> Result<Vec<User>, VozError> {
+        let document = self.fetch(self.client.get("/account/ignored")).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_ignored_members(node)
+    }
+
+    pub async fn ignore_member(&self, user_id: String) -> Result<(), VozError> {
+        self.set_ignored(user_id, true).await
+    }
+
+    pub async fn unignore_member(&self, user_id: String) -> Result<(), VozError> {
+        self.set_ignored(user_id, false).await
+    }
+
+    /// XenForo's ignore action toggles, so it is only sent when the current state differs.
+    async fn set_ignored(&self, user_id: String, ignored: bool) -> Result<(), VozError> {
+        let is_ignored = self.get_ignored_members().await?.iter().any(|u| u.id == user_id);
+        if is_ignored == ignored {
+            return Ok(());
+        }
+        let form = HashMap::from([("_xfToken", self.csrf().await?)]);
+        self.fetch_json(self.client.post(format!("/u/{user_id}/ignore")).form(&form)).await?;
+        Ok(())
+    }
+
+    pub async fn get_thread(&self, id: String, page: Option<i64>) -> Result<Thread, VozError> {
         let uri = match page {
             Some(p) => format!("page-{p}"),
             None => "unread".to_string()
         };
-        let content = self.client.get(format!("/t/{id}/{uri}")).send().await?.text().await?;
-        let document = Document::from_read(content.as_bytes()).ok().ok_or("Invalid request")?;
-        let node = document.nth(0).ok_or("p-body does not exist")?;
+        let document = self.fetch(self.client.get(format!("/t/{id}/{uri}"))).await?;
+        let node = document.nth(0).or_parse("html", "p-body does not exist")?;
         let result = parse_thread_detail(node)?;
         Ok(result)
     }
+
+    /// Prefixes that can be picked when starting a thread in the forum.
+    pub async fn get_thread_prefixes(&self, forum_id: String) -> Result<Vec<ThreadPrefix>, VozError> {
+        let (_, prefixes) = self.post_thread_form(&forum_id).await?;
+        Ok(prefixes)
+    }
+
+    /// Starts a thread in the forum and returns its id with the first post.
+    pub async fn create_thread(&self, forum_id: String, title: String, prefix_id: Option<String>, message: MessageContent, options: ThreadOptions) -> Result<NewThread, VozError> {
+        let (editor, prefixes) = self.post_thread_form(&forum_id).await?;
+        if prefix_id.as_ref().is_some_and(|id| !prefixes.iter().any(|p| &p.id == id)) {
+            return Err(VozError::InvalidArgument { message: "prefix_id is not offered in this forum".to_string() });
+        }
+        let url = editor.url.clone();
+        let mut form = editor_fields(editor, message, options.attachment_hash);
+        form.insert("title".to_string(), title);
+        form.insert("prefix_id".to_string(), prefix_id.unwrap_or("0".to_string()));
+        if options.watch {
+            form.insert("watch_thread".to_string(), "1".to_string());
+            if options.watch_email {
+                form.insert("watch_thread_email".to_string(), "1".to_string());
+            }
+        }
+        let response = self.fetch_json(self.client.post(url).form(&form)).await?;
+        // Threads awaiting approval redirect back to the forum instead
+        let redirect = response.redirect.filter(|r| r.contains("/t/") || r.contains("/threads/"))
+                        .ok_or_else(|| VozError::XenForo { message: response.message.unwrap_or("Your thread was not created".to_string()) })?;
+        let thread_id = parse_content_id(&redirect).or_parse("redirect", "Cannot find new thread id")?;
+        let thread = self.get_thread(thread_id.clone(), Some(1)).await?;
+        let post = thread.posts.into_iter().next().or_parse("article.js-post", "Not found first post")?;
+        Ok(NewThread { thread_id, post })
+    }
+
+    async fn post_thread_form(&self, forum_id: &str) -> Result<(EditorForm, Vec<ThreadPrefix>), VozError> {
+        let document = self.fetch(self.client.get(format!("/forums/{forum_id}/post-thread"))).await?;
+        let node = document.find(Name("form")).find(|n| n.attr("action").is_some_and(|a| a.contains("/post-thread"))).ok_or(VozError::PermissionDenied { message: "You cannot post threads in this forum".to_string() })?;
+        Ok((parse_editor_form(node)?, parse_thread_prefixes(node)))
+    }
+
+    /// Votes for `option_ids` in the thread's poll and returns the poll with the updated results.
+    pub async fn vote_poll(&self, thread_id: String, option_ids: Vec<String>) -> Result<Poll, VozError> {
+        let mut form = vec![("_xfToken", self.csrf().await?)];
+        form.extend(option_ids.into_iter().map(|id| ("responses[]", id)));
+        let response = self.fetch_json(self.client.post(format!("/t/{thread_id}/poll/vote")).form(&form)).await?;
+        let content = response.html.and_then(|h| h.content).unwrap_or_default();
+        if let Some(poll) = Document::from(content.as_str()).nth(0).and_then(parse_poll) {
+            return Ok(poll);
+        }
+        self.get_thread(thread_id, Some(1)).await?.poll.or_parse("form.js-pollContainer", "Not found poll")
+    }
+
+    /// Posts `message` through the quick reply form of the thread and returns the created post.
+    /// `attachment_hash` overrides the form's hash when attachments were uploaded beforehand.
+    pub async fn reply(&self, thread_id: String, message: MessageContent, attachment_hash: Option<String>) -> Result<Post, VozError> {
+        self.submit_quick_reply(format!("/t/{thread_id}/latest"), message, attachment_hash).await
+    }
+
+    /// Loads `page_url`, submits its quick reply form and returns the message that was created.
+    async fn submit_quick_reply(&self, page_url: String, message: MessageContent, attachment_hash: Option<String>) -> Result<Post, VozError> {
+        let document = self.fetch(self.client.get(page_url)).await?;
+        let node = document.find(And(Name("form"), Class("js-quickReply"))).next().ok_or(VozError::PermissionDenied { message: "You cannot reply here".to_string() })?;
+        let editor = parse_editor_form(node)?;
+        let url = editor.url.clone();
+        let form = editor_fields(editor, message, attachment_hash);
+        let response = self.fetch_json(self.client.post(url).form(&form)).await?;
+        let content = response.html.and_then(|h| h.content).unwrap_or_default();
+        let document = Document::from(content.as_str());
+        if let Some(post) = document.find(message_article()).filter_map(|n| parse_post(n).ok()).max_by_key(|p| p.post_id.parse::<i64>().unwrap_or(0)) {
+            return Ok(post);
+        }
+        let redirect = response.redirect.or_parse("redirect", "Reply response has neither html nor redirect")?;
+        self.get_post(redirect).await
+    }
+
+    /// Raw BBCode of one of the visitor's posts, as shown in the edit form.
+    pub async fn get_post_for_edit(&self, post_id: String) -> Result<String, VozError> {
+        let document = self.fetch(self.client.get(format!("/p/{post_id}/edit"))).await?;
+        let node = edit_form(&document)?;
+        parse_editor_message(node).or_parse("textarea[name=message]", "Not found post message")
+    }
+
+    /// Replaces the post's message and returns the post as rendered after the edit.
+    pub async fn edit_post(&self, post_id: String, message: MessageContent, reason: Option<String>) -> Result<Post, VozError> {
+        let document = self.fetch(self.client.get(format!("/p/{post_id}/edit"))).await?;
+        let editor = parse_editor_form(edit_form(&document)?)?;
+        let url = editor.url.clone();
+        let mut form = editor_fields(editor, message, None);
+        if let Some(reason) = reason {
+            form.insert("edit_reason".to_string(), reason);
+        }
+        self.fetch_json(self.client.post(url).form(&form)).await?;
+        self.get_post(format!("/p/{post_id}/")).await
+    }
+
+    /// Deletes the post, `hard` removes it permanently instead of leaving it visible to moderators.
+    pub async fn delete_post(&self, post_id: String, reason: Option<String>, hard: bool) -> Result<DeletedPost, VozError> {
+        let mut form = HashMap::from([("_xfToken", self.csrf().await?), ("reason", reason.clone().unwrap_or_default())]);
+        if hard {
+            form.insert("hard_delete", "1".to_string());
+        }
+        self.fetch_json(self.client.post(format!("/p/{post_id}/delete")).form(&form)).await?;
+        Ok(DeletedPost { post_id, hard_deleted: hard, reason })
+    }
+
+    /// Quote BBCode of a post as generated by XenForo's reply button.
+    pub async fn get_quote(&self, post_id: String) -> Result<String, VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?)]);
+        let response = self.fetch_json(self.client.post(format!("/p/{post_id}/quote")).form(&form)).await?;
+        response.extra.get("quote").and_then(|v| v.as_str()).map(|s| s.to_string()).or_parse("quote", "Response has no quote")
+    }
+
+    /// Quote BBCode of several posts of a thread through the multi-quote endpoint, in the order of `post_ids`.
+    pub async fn get_multi_quote(&self, thread_id: String, post_ids: Vec<String>) -> Result<String, VozError> {
+        let selection = post_ids.iter().map(|id| (id.clone(), serde_json::json!([true]))).collect::<serde_json::Map<String, serde_json::Value>>();
+        let form = HashMap::from([
+            ("_xfToken", self.csrf().await?),
+            ("quotes", serde_json::Value::Object(selection).to_string()),
+            ("insert", "1".to_string())
+        ]);
+        let response = self.fetch_json(self.client.post(format!("/t/{thread_id}/multi-quote")).form(&form)).await?;
+        let quotes = response.extra.get("quotes").or_parse("quotes", "Response has no quotes")?;
+        let result = post_ids.iter().filter_map(|id| quotes.get(id)).flat_map(json_strings).collect::<Vec<String>>();
+        if result.is_empty() {
+            return Err(VozError::parse("quotes", "Response has no quotes"));
+        }
+        Ok(result.join("\n"))
+    }
+
+    /// Uploads a file through XenForo's attachment manager. It is attached to the message sent with `context.hash`.
+    pub async fn upload_attachment(&self, context: &AttachmentContext, bytes: Vec<u8>, filename: String, mime: String) -> Result<Attachment, VozError> {
+        let part = Part::bytes(bytes).file_name(filename).mime_str(&mime).map_err(|_| VozError::InvalidArgument { message: format!("{mime} is not a valid MIME type") })?;
+        let form = Form::new().text("_xfToken", self.csrf().await?).part("upload", part);
+        let mut query = vec![("type".to_string(), context.content_type.clone()), ("hash".to_string(), context.hash.clone())];
+        query.extend(context.context.iter().map(|(k, v)| (format!("context[{k}]"), v.clone())));
+        let response = self.fetch_json(self.client.post("/attachments/upload").query(&query).multipart(form)).await?;
+        let attachment = response.extra.get("attachment").or_parse("attachment", "Upload response has no attachment")?;
+        let field = |key: &str| attachment.get(key).map(|v| v.as_str().map(|s| s.to_string()).unwrap_or(v.to_string())).filter(|s| !s.is_empty() && s != "null");
+        Ok(Attachment {
+            attachment_id: field("attachment_id").or_parse("attachment.attachment_id", "Not found attachment id")?,
+            filename: field("filename").unwrap_or_default(),
+            thumbnail_url: field("thumbnail_url"),
+            link: field("link").unwrap_or_default()
+        })
+    }
+
+    /// Applies `reaction_id` to the post and returns the refreshed reactions bar.
+    pub async fn react(&self, post_id: String, reaction_id: i64) -> Result<Option<ReactionSummary>, VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?)]);
+        let request = self.client.post(format!("/p/{post_id}/react")).query(&[("reaction_id", reaction_id)]).form(&form);
+        let response = self.fetch_json(request).await?;
+        let content = response.extra.get("reactionList").and_then(|v| v.get("content")).and_then(|v| v.as_str()).unwrap_or_default();
+        let document = Document::from(content);
+        Ok(document.nth(0).and_then(parse_reactions))
+    }
+
+    /// Removes the visitor's reaction. XenForo toggles a reaction off when the same one is sent again,
+    /// so the current reaction is read from the post first.
+    pub async fn unreact(&self, post_id: String) -> Result<Option<ReactionSummary>, VozError> {
+        let post = self.get_post(format!("/p/{post_id}/")).await?;
+        match post.visitor_reaction_id {
+            Some(reaction_id) => self.react(post_id, reaction_id).await,
+            None => Ok(post.reactions)
+        }
+    }
+
+    /// Lists who reacted to a post, optionally only with `reaction_id`.
+    pub async fn get_post_reactions(&self, post_id: String, reaction_id: Option<i64>, page: i64) -> Result<PostReactions, VozError> {
+        let query = [("reaction_id", reaction_id.unwrap_or(0)), ("page", page)];
+        let document = self.fetch(self.client.get(format!("/p/{post_id}/reactions")).query(&query)).await?;
+        let node = document.nth(0).or_parse("html", "Reaction list does not exist")?;
+        parse_post_reactions(node, page)
+    }
+
+    /// Loads the page a post or conversation message link points to and parses that message.
+    async fn get_post(&self, url: String) -> Result<Post, VozError> {
+        let post_id = parse_post_id_from_url(&url).or_parse("redirect", "Cannot find post id in url")?;
+        let document = self.fetch(self.client.get(url)).await?;
+        document.find(message_article()).filter_map(|n| parse_post(n).ok()).find(|p| p.post_id == post_id).or_parse("article.js-post", "Not found post")
+    }
+
+    /// Bookmarks the post, or updates the labels and note of an existing bookmark.
+    pub async fn bookmark(&self, post_id: String, labels: Vec<String>, note: Option<String>) -> Result<(), VozError> {
+        let form = HashMap::from([
+            ("_xfToken", self.csrf().await?),
+            ("labels", labels.join(", ")),
+            ("message", note.unwrap_or_default())
+        ]);
+        self.fetch_json(self.client.post(format!("/p/{post_id}/bookmark")).form(&form)).await?;
+        Ok(())
+    }
+
+    pub async fn remove_bookmark(&self, post_id: String) -> Result<(), VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?), ("delete", "1".to_string())]);
+        self.fetch_json(self.client.post(format!("/p/{post_id}/bookmark")).form(&form)).await?;
+        Ok(())
+    }
+
+    /// Lists the user's bookmarks, only those tagged with `label` when given.
+    pub async fn get_bookmarks(&self, label: Option<String>, page: i64) -> Result<BookmarkList, VozError> {
+        let mut query = vec![("page", page.to_string())];
+        if let Some(label) = label {
+            query.push(("label", label));
+        }
+        let document = self.fetch(self.client.get("/account/bookmarks").query(&query)).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_bookmarks(node)
+    }
+
+    pub async fn find_threads(&self, kind: FindThreadsKind, page: i64) -> Result<ThreadList, VozError> {
+        let document = self.fetch(self.client.get(format!("/find-threads/{}", kind.path())).query(&[("page", page)])).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_thread_list(node)
+    }
+
+    pub async fn get_watched_threads(&self, page: i64) -> Result<ThreadList, VozError> {
+        let document = self.fetch(self.client.get("/watched/threads").query(&[("page", page)])).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_thread_list(node)
+    }
+
+    pub async fn get_watched_forums(&self) -> Result<Vec<ForumItem>, VozError> {
+        let document = self.fetch(self.client.get("/watched/forums")).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        let results = node.find(Class("node")).filter_map(|x| parse_forum_item(x).ok()).collect::<Vec<ForumItem>>();
+        Ok(results)
+    }
+
+    /// Watches the thread, `email_subscribe` also sends new replies by email.
+    pub async fn watch_thread(&self, id: String, email_subscribe: bool) -> Result<(), VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?), ("email_subscribe", (email_subscribe as i64).to_string())]);
+        self.fetch_json(self.client.post(format!("/t/{id}/watch")).form(&form)).await?;
+        Ok(())
+    }
+
+    pub async fn unwatch_thread(&self, id: String) -> Result<(), VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?), ("stop", "1".to_string())]);
+        self.fetch_json(self.client.post(format!("/t/{id}/watch")).form(&form)).await?;
+        Ok(())
+    }
+
+    /// Watches or stops watching a forum. Watching alerts on new threads, and emails them too when `send_email` is set.
+    pub async fn watch_forum(&self, id: String, watch: bool, send_email: bool) -> Result<(), VozError> {
+        let mut form = HashMap::from([("_xfToken", self.csrf().await?)]);
+        if watch {
+            form.insert("notify", "thread".to_string());
+            form.insert("send_alert", "1".to_string());
+            form.insert("send_email", (send_email as i64).to_string());
+        } else {
+            form.insert("stop", "1".to_string());
+        }
+        self.fetch_json(self.client.post(format!("/f/{id}/watch")).form(&form)).await?;
+        Ok(())
+    }
+
+    /// Loads a What's New feed. New posts and profile posts are filtered lists XenForo redirects to a find-new id,
+    /// later pages are read from that id, a page past the last one is returned empty. Activity feeds have no pages,
+    /// pass the `before_id` of the previous `ActivityList` to load older items.
+    pub async fn get_whats_new(&self, kind: WhatsNewKind, unread_only: bool, page: i64, before_id: Option<String>) -> Result<WhatsNew, VozError> {
+        let path = format!("/whats-new/{}/", kind.path());
+        let mut request = self.client.get(&path);
+        if unread_only {
+            request = request.query(&[("unread", 1)]);
+        }
+        if let (WhatsNewKind::LatestActivity | WhatsNewKind::NewsFeed, Some(before_id)) = (kind, before_id) {
+            request = request.query(&[("before_id", before_id)]);
+        }
+        let mut document = self.fetch(request).await?;
+        if matches!(kind, WhatsNewKind::Posts | WhatsNewKind::ProfilePosts) && page > 1 {
+            let find_new_id = document.find(Class("pageNav").descendant(Name("a"))).filter_map(|n| n.attr("href")).find_map(parse_find_new_id);
+            let Some(id) = find_new_id else {
+                // XenForo only stores the filter when there is more than one page
+                return Ok(match kind {
+                    WhatsNewKind::Posts => WhatsNew::Threads(ThreadList { threads: vec![], current_page: page.to_string(), total_page: "1".to_string() }),
+                    _ => WhatsNew::Activities(ActivityList { activities: vec![], current_page: page, has_more: false, before_id: None })
+                });
+            };
+            document = self.fetch(self.client.get(format!("{path}{id}/page-{page}"))).await?;
+        }
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        match kind {
+            WhatsNewKind::Posts => Ok(WhatsNew::Threads(parse_thread_list(node)?)),
+            _ => Ok(WhatsNew::Activities(parse_activities(node, page)?))
+        }
+    }
+
+    /// Runs a search for `query` and returns the first page of results. XenForo answers the search form with
+    /// a redirect to `/search/{id}/`, that id is returned so later pages are loaded with `get_search_page`
+    /// instead of searching again, which XenForo rate limits.
+    pub async fn search(&self, query: String, options: SearchOptions) -> Result<SearchResults, VozError> {
+        let mut form = vec![
+            ("_xfToken", self.csrf().await?),
+            ("keywords", query.clone()),
+            ("search_type", "post".to_string()),
+            ("order", options.order.value().to_string())
+        ];
+        if options.group_by_thread {
+            form.push(("grouped", "1".to_string()));
+        }
+        form.extend(search_constraints(&options));
+        let (document, url) = self.fetch_page(self.client.post("/search/search").form(&form)).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        let results = parse_search_results(node)?;
+        Ok(SearchResults { search_id: parse_search_id(url.path()).or(results.search_id), query, options, ..results })
+    }
+
+    /// Loads another page of a search. The query and options are sent along like XenForo's own page links,
+    /// so an expired search is run again and the new search id is returned.
+    pub async fn get_search_page(&self, search_id: String, query: String, options: SearchOptions, page: i64) -> Result<SearchResults, VozError> {
+        let mut params = vec![
+            ("page", page.to_string()),
+            ("q", query.clone()),
+            ("t", "post".to_string()),
+            ("o", options.order.value().to_string())
+        ];
+        if options.group_by_thread {
+            params.push(("g", "1".to_string()));
+        }
+        params.extend(search_constraints(&options));
+        let (document, url) = self.fetch_page(self.client.get(format!("/search/{search_id}/")).query(&params)).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        let results = parse_search_results(node)?;
+        Ok(SearchResults { search_id: parse_search_id(url.path()).or(Some(search_id)), query, options, ..results })
+    }
+
+    pub async fn get_alerts(&self, page: i64) -> Result<AlertList, VozError> {
+        let document = self.fetch(self.client.get("/account/alerts").query(&[("page", page)])).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_alerts(node)
+    }
+
+    pub async fn mark_alerts_read(&self) -> Result<(), VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?)]);
+        self.fetch_json(self.client.post("/account/alerts/mark-read").form(&form)).await?;
+        Ok(())
+    }
+
+    pub async fn get_conversations(&self, page: i64) -> Result<ConversationList, VozError> {
+        let document = self.fetch(self.client.get(format!("/conversations/page-{page}"))).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_conversations(node)
+    }
+
+    pub async fn get_conversation(&self, id: String, page: Option<i64>) -> Result<Conversation, VozError> {
+        let uri = match page {
+            Some(p) => format!("page-{p}"),
+            None => "unread".to_string()
+        };
+        let document = self.fetch(self.client.get(format!("/conversations/{id}/{uri}"))).await?;
+        let node = document.find(Class("p-body")).next().or_parse(".p-body", "p-body does not exist")?;
+        parse_conversation_detail(node)
+    }
+
+    pub async fn reply_conversation(&self, id: String, message: MessageContent, attachment_hash: Option<String>) -> Result<Post, VozError> {
+        self.submit_quick_reply(format!("/conversations/{id}/latest"), message, attachment_hash).await
+    }
+
+    /// Starts a conversation with `recipients` (user names) and returns its id.
+    pub async fn start_conversation(&self, recipients: Vec<String>, title: String, message: MessageContent, open_invite: bool, locked: bool) -> Result<String, VozError> {
+        let document = self.fetch(self.client.get("/conversations/add")).await?;
+        let node = document.find(Name("form")).find(|n| n.attr("action").is_some_and(|a| a.contains("/conversations/add"))).ok_or(VozError::PermissionDenied { message: "You cannot start conversations".to_string() })?;
+        let editor = parse_editor_form(node)?;
+        let url = editor.url.clone();
+        let mut form = editor_fields(editor, message, None);
+        form.insert("recipients".to_string(), recipients.join(", "));
+        form.insert("title".to_string(), title);
+        if open_invite {
+            form.insert("conversation_open_invite".to_string(), "1".to_string());
+        }
+        if locked {
+            form.insert("conversation_locked".to_string(), "1".to_string());
+        }
+        let response = self.fetch_json(self.client.post(url).form(&form)).await?;
+        response.redirect.as_deref().and_then(parse_content_id).or_parse("redirect", "Cannot find new conversation id")
+    }
+
+    /// Leaves the conversation, `ignore_future` also rejects future messages in it.
+    pub async fn leave_conversation(&self, id: String, ignore_future: bool) -> Result<(), VozError> {
+        let state = if ignore_future { "deleted_ignored" } else { "deleted" };
+        let form = HashMap::from([("_xfToken", self.csrf().await?), ("recipient_state", state.to_string())]);
+        self.fetch_json(self.client.post(format!("/conversations/{id}/leave")).form(&form)).await?;
+        Ok(())
+    }
+
+    /// XenForo marks a conversation read once its last page has been viewed.
+    pub async fn mark_conversation_read(&self, id: String) -> Result<(), VozError> {
+        self.fetch(self.client.get(format!("/conversations/{id}/latest"))).await?;
+        Ok(())
+    }
+
+    pub async fn mark_conversation_unread(&self, id: String) -> Result<(), VozError> {
+        let form = HashMap::from([("_xfToken", self.csrf().await?)]);
+        self.fetch_json(self.client.post(format!("/conversations/{id}/mark-unread")).form(&form)).await?;
+        Ok(())
+    }
+}
+
+/// Thread posts and conversation messages.
+fn message_article() -> And<Name<&'static str>, Or<Class<&'static str>, Class<&'static str>>> {
+    And(Name("article"), Or(Class("js-post"), Class("js-message")))
+}
+
+/// The edit form of a post, missing when the visitor may not edit it.
+fn edit_form(document: &Document) -> Result<Node<'_>, VozError> {
+    document.find(Name("form")).find(|n| n.attr("action").is_some_and(|a| a.ends_with("/edit")))
+        .ok_or(VozError::PermissionDenied { message: "You cannot edit this post".to_string() })
+}
+
+/// Hidden inputs of an editor form with the message and attachment hash filled in.
+fn editor_fields(editor: EditorForm, message: MessageContent, attachment_hash: Option<String>) -> HashMap<String, String> {
+    let mut form = editor.hidden_fields;
+    let (key, value) = message.field();
+    form.insert(key.to_string(), value);
+    if let Some(hash) = attachment_hash {
+        if let (Some(combined), Some(old_hash)) = (form.get_mut("attachment_hash_combined"), editor.attachment_hash.as_deref()) {
+            *combined = combined.replace(old_hash, &hash);
+        }
+        form.insert("attachment_hash".to_string(), hash);
+    }
+    form
+}
+
+/// Profile tabs are extras, a member can hide them from visitors without the profile being private.
+/// Only that refusal is read as an empty tab, other failures are returned.
+fn hidden_tab(result: Result<Document, VozError>) -> Result<Option<Document>, VozError> {
+    match result {
+        Ok(document) => Ok(Some(document)),
+        Err(VozError::PermissionDenied { .. } | VozError::HttpStatus { status: 403 | 404 }) => Ok(None),
+        Err(error) => Err(error)
+    }
+}
+
+/// The `c[...]` constraints shared by the search form and the result page links.
+fn search_constraints(options: &SearchOptions) -> Vec<(&'static str, String)> {
+    let mut constraints = vec![];
+    if options.title_only {
+        constraints.push(("c[title_only]", "1".to_string()));
+    }
+    if !options.members.is_empty() {
+        constraints.push(("c[users]", options.members.join(", ")));
+    }
+    constraints.extend(options.forum_ids.iter().map(|id| ("c[nodes][]", id.clone())));
+    if options.include_sub_forums {
+        constraints.push(("c[child_nodes]", "1".to_string()));
+    }
+    if let Some(date) = &options.newer_than {
+        constraints.push(("c[newer_than]", date.clone()));
+    }
+    if let Some(date) = &options.older_than {
+        constraints.push(("c[older_than]", date.clone()));
+    }
+    constraints
+}
+
+/// Every string inside a json value, depth first. XenForo keys the quotes of a post by selection index, those
+/// keys are read in numeric order since `serde_json::Map` sorts them as strings ("10" before "2").
+fn json_strings(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(s) => vec![s.clone()],
+        serde_json::Value::Array(items) => items.iter().flat_map(json_strings).collect(),
+        serde_json::Value::Object(items) => {
+            let mut entries = items.iter().collect::<Vec<(&String, &serde_json::Value)>>();
+            entries.sort_by_key(|(key, _)| key.parse::<u64>().unwrap_or(u64::MAX));
+            entries.into_iter().flat_map(|(_, value)| json_strings(value)).collect()
+        },
+        _ => vec![]
+    }
+}
+
+/// Maps an error status/message pair from XenForo to the matching `VozError` variant.
+fn classify_error(status: StatusCode, message: Option<String>, is_login_page: bool, retry_after: Option<u64>) -> VozError {
+    let text = message.clone().unwrap_or_default().to_lowercase();
+    if status == StatusCode::TOO_MANY_REQUESTS || text.contains("must wait at least") {
+        let retry_after = retry_after.or_else(|| text.split_whitespace().find_map(|w| w.parse::<u64>().ok()));
+        return VozError::RateLimited { message: message.unwrap_or("Too many requests".to_string()), retry_after };
+    }
+    if text.contains("time limit") || text.contains("may only edit") {
+        return VozError::TimeLimitExpired { message: message.unwrap_or_default() };
+    }
+    if status == StatusCode::UNAUTHORIZED || is_login_page || text.contains("must be logged-in") {
+        return VozError::NotLoggedIn;
+    }
+    if status == StatusCode::FORBIDDEN || text.contains("do not have permission") {
+        return VozError::PermissionDenied { message: message.unwrap_or("You do not have permission to view this page or perform this action.".to_string()) };
+    }
+    match message {
+        Some(message) => VozError::XenForo { message },
+        None => VozError::HttpStatus { status: status.as_u16() }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use std::io::prelude::*;
 
     #[tokio::test]
@@ -162,6 +770,276 @@ mod tests {
         println!("{:?}", result);
     }
 
+    #[test]
+    fn test_failed_response() {
+        let result: Result<User, VozError> = Err(VozError::NotLoggedIn);
+        let json = serde_json::to_value(result.voz_response()).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["code"], "not_logged_in");
+        assert_eq!(json["message"], "Not login yet");
+    }
+
+    #[test]
+    fn test_classify_error() {
+        let error = classify_error(StatusCode::BAD_REQUEST, Some("You must wait at least 30 seconds before performing this action.".to_string()), false, None);
+        assert!(matches!(error, VozError::RateLimited { retry_after: Some(30), .. }));
+        let error = classify_error(StatusCode::FORBIDDEN, Some("You do not have permission to view this page or perform this action.".to_string()), false, None);
+        assert_eq!(error.code(), "permission_denied");
+        let error = classify_error(StatusCode::FORBIDDEN, Some("The time limit for editing this message has expired.".to_string()), false, None);
+        assert_eq!(error.code(), "time_limit_expired");
+        let error = classify_error(StatusCode::BAD_REQUEST, Some("Please enter a valid message.".to_string()), false, None);
+        assert_eq!(error.code(), "xenforo");
+    }
+
+    const CSRF_PAGE: &str = r#"<html data-csrf="1703425862,token"><body></body></html>"#;
+
+    /// Answers `count` requests on a local port, `handler` maps each raw request to a status line (followed by any
+    /// extra header lines) and a body. The server task returns the raw requests it received.
+    async fn mock_server(count: usize, handler: fn(&str) -> (&'static str, String)) -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let mut requests = vec![];
+            while requests.len() < count {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![];
+                let mut buffer = [0; 4096];
+                loop {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head.lines().find_map(|l| l.to_lowercase().strip_prefix("content-length: ").and_then(|v| v.parse::<usize>().ok())).unwrap_or(0);
+                        if body.len() >= length || read == 0 {
+                            break;
+                        }
+                    }
+                }
+                let text = String::from_utf8_lossy(&request).to_string();
+                let (status, body) = handler(&text);
+                let response = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+                socket.write_all(response.as_bytes()).await.unwrap();
+                requests.push(text);
+            }
+            requests
+        });
+        (port, server)
+    }
+
+    #[tokio::test]
+    async fn test_upload_attachment() {
+        let (port, server) = mock_server(2, |request| if request.starts_with("GET") {
+            ("200 OK", CSRF_PAGE.to_string())
+        } else {
+            ("200 OK", r#"{"status":"ok","attachment":{"attachment_id":4321,"filename":"cat.png","thumbnail_url":"https://data.voz.vn/attachments/4/4321-cat.jpg","link":"/attachments/cat-png.4321/"}}"#.to_string())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let context = AttachmentContext::reply("896639".to_string());
+        let attachment = core.upload_attachment(&context, b"png bytes".to_vec(), "cat.png".to_string(), "image/png".to_string()).await.unwrap();
+        assert_eq!(attachment.attachment_id, "4321");
+        assert_eq!(attachment.thumbnail_url, Some("https://data.voz.vn/attachments/4/4321-cat.jpg".to_string()));
+        assert_eq!(attachment.bbcode(), "[ATTACH]4321[/ATTACH]");
+
+        let requests = server.await.unwrap();
+        let upload = &requests[1];
+        assert!(upload.starts_with("POST /attachments/upload?"));
+        assert!(upload.contains(&format!("hash={}", context.hash)));
+        assert!(upload.contains("context%5Bthread_id%5D=896639"));
+        assert!(upload.contains("name=\"upload\"; filename=\"cat.png\""));
+        assert!(upload.contains("1703425862,token"));
+        assert!(upload.contains("png bytes"));
+
+        // Checked before anything is sent, the address is never reached
+        let core = VozCore::new("http://127.0.0.1:9".to_string());
+        let error = core.upload_attachment(&context, vec![], "cat.png".to_string(), "image png".to_string()).await.unwrap_err();
+        assert_eq!(error.code(), "invalid_argument");
+    }
+
+    #[tokio::test]
+    async fn test_reply() {
+        let (port, server) = mock_server(3, |request| if request.starts_with("GET") {
+            ("200 OK", fs::read_to_string("resources/tests/thread.html").unwrap())
+        } else {
+            ("200 OK", r#"{"status":"ok","redirect":"/p/29542846/"}"#.to_string())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let post = core.reply("896639".to_string(), MessageContent::BBCode("Bắt là đúng".to_string()), None).await.unwrap();
+        assert_eq!(post.post_id, "29542846");
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("GET /t/896639/latest "));
+        let submit = &requests[1];
+        assert!(submit.starts_with("POST /t/tiktoker-55-8-trieu-nguoi-theo-doi-bi-bat.896639/add-reply?"));
+        // the token comes from the quick reply form of the page loaded first
+        assert!(submit.contains("_xfToken=1703425862%2Ccc7c9335c5b8efe00ef55af56de0e83f"));
+        assert!(submit.contains("message=B%E1%BA%AFt+l%C3%A0+%C4%91%C3%BAng"));
+        assert!(requests[2].starts_with("GET /p/29542846/ "));
+    }
+
+    fn react_response() -> String {
+        let content = r#"<div class="reactionsBar js-reactionsList is-active"><ul class="reactionSummary"><li><span class="reaction reaction--small reaction--1" data-reaction-id="1"><i aria-hidden="true"></i><img src="https://statics.voz.tech/styles/next/xenforo/reactions/popo/sweet_kiss.png?v=01" class="reaction-image js-reaction" alt="Ưng" title="Ưng"></span></li></ul><span class="u-srOnly">Reactions:</span><a class="reactionsBar-link" href="/p/29542846/reactions" data-xf-click="overlay" data-cache="false" rel="nofollow"><bdi>bboyviet</bdi>, <bdi>Phanh Blank</bdi>, <bdi>takmasidai</bdi> and 4 others</a></div>"#;
+        serde_json::json!({ "status": "ok", "reactionList": { "content": content }, "linkReactionId": 1 }).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_react() {
+        let (port, server) = mock_server(2, |request| if request.starts_with("GET") {
+            ("200 OK", CSRF_PAGE.to_string())
+        } else {
+            ("200 OK", react_response())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let reactions = core.react("29542846".to_string(), 1).await.unwrap().unwrap();
+        assert_eq!(reactions.icons, vec!["https://statics.voz.tech/styles/next/xenforo/reactions/popo/sweet_kiss.png?v=01"]);
+        assert_eq!(reactions.message, "bboyviet, Phanh Blank, takmasidai and 4 others");
+
+        let requests = server.await.unwrap();
+        assert!(requests[1].starts_with("POST /p/29542846/react?reaction_id=1"));
+        assert!(requests[1].contains("_xfResponseType=json"));
+    }
+
+    #[tokio::test]
+    async fn test_unreact() {
+        let (port, server) = mock_server(3, |request| match request.split_whitespace().nth(1).unwrap_or_default() {
+            // the visitor reacted with "Ưng" to the post
+            "/p/29542846/" => {
+                let content = fs::read_to_string("resources/tests/thread.html").unwrap();
+                let (head, tail) = content.split_once("/p/29542846/react?reaction_id=1").unwrap();
+                ("200 OK", format!("{head}/p/29542846/react?reaction_id=1{}", tail.replacen("actionBar-action--reaction", "actionBar-action--reaction has-reaction", 1)))
+            },
+            "/" => ("200 OK", CSRF_PAGE.to_string()),
+            _ => ("200 OK", react_response())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let reactions = core.unreact("29542846".to_string()).await.unwrap();
+        assert!(reactions.is_some());
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("GET /p/29542846/ "));
+        assert!(requests[2].starts_with("POST /p/29542846/react?reaction_id=1"));
+    }
+
+    #[tokio::test]
+    async fn test_quotes() {
+        let (port, server) = mock_server(3, |request| match request.split_whitespace().nth(1).unwrap_or_default() {
+            "/" => ("200 OK", CSRF_PAGE.to_string()),
+            path if path.starts_with("/p/29542846/quote") => ("200 OK", r#"{"status":"ok","quote":"[QUOTE=\"BesideU, post: 29542846, member: 1861037\"]Bắt là đúng[/QUOTE]\n"}"#.to_string()),
+            _ => ("200 OK", r#"{"status":"ok","quotes":{"29542846":{"2":"[QUOTE=\"BesideU, post: 29542846, member: 1861037\"]hai[/QUOTE]","10":"[QUOTE=\"BesideU, post: 29542846, member: 1861037\"]mười[/QUOTE]"},"29542891":["[QUOTE=\"xinata, post: 29542891, member: 1673470\"]ok[/QUOTE]"]}}"#.to_string())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let quote = core.get_quote("29542846".to_string()).await.unwrap();
+        assert_eq!(quote, "[QUOTE=\"BesideU, post: 29542846, member: 1861037\"]Bắt là đúng[/QUOTE]\n");
+        let quotes = core.get_multi_quote("896639".to_string(), vec!["29542891".to_string(), "29542846".to_string()]).await.unwrap();
+        assert_eq!(quotes, [
+            "[QUOTE=\"xinata, post: 29542891, member: 1673470\"]ok[/QUOTE]",
+            "[QUOTE=\"BesideU, post: 29542846, member: 1861037\"]hai[/QUOTE]",
+            "[QUOTE=\"BesideU, post: 29542846, member: 1861037\"]mười[/QUOTE]"
+        ].join("\n"));
+
+        let requests = server.await.unwrap();
+        assert!(requests[2].starts_with("POST /t/896639/multi-quote?"));
+        assert!(requests[2].contains("insert=1"));
+    }
+
+    #[tokio::test]
+    async fn test_member_hidden_tabs() {
+        let (port, server) = mock_server(3, |request| match request.split_whitespace().nth(1).unwrap_or_default() {
+            "/u/1234567/" => ("200 OK", fs::read_to_string("resources/tests/member.html").unwrap()),
+            _ => ("403 Forbidden", r#"<html><body><div class="blockMessage">This member limits who may view their full profile.</div></body></html>"#.to_string())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let profile = core.get_member("1234567".to_string()).await.unwrap();
+        assert!(profile.about.is_none());
+        assert!(profile.recent_activity.is_empty());
+        assert_eq!(server.await.unwrap().len(), 3);
+
+        let (port, _) = mock_server(2, |request| match request.split_whitespace().nth(1).unwrap_or_default() {
+            "/u/1234567/" => ("200 OK", fs::read_to_string("resources/tests/member.html").unwrap()),
+            _ => ("429 Too Many Requests\r\nRetry-After: 30", String::new())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let result = core.get_member("1234567".to_string()).await;
+        assert!(matches!(result, Err(VozError::RateLimited { retry_after: Some(30), .. })));
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let (port, server) = mock_server(6, |request| match request.split_whitespace().nth(1).unwrap_or_default() {
+            "/" => ("200 OK", CSRF_PAGE.to_string()),
+            "/search/search" => ("303 See Other\r\nLocation: /search/51239999/?q=b%C3%A0n+ph%C3%ADm&o=date", String::new()),
+            path if path.starts_with("/search/51239999/?page=3") => ("303 See Other\r\nLocation: /search/51240000/?page=3&q=b%C3%A0n+ph%C3%ADm&o=date", String::new()),
+            _ => ("200 OK", fs::read_to_string("resources/tests/search.html").unwrap())
+        }).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let options = SearchOptions { order: SearchOrder::Date, forum_ids: vec!["33".to_string()], ..SearchOptions::default() };
+        let results = core.search("bàn phím".to_string(), options).await.unwrap();
+        assert_eq!(results.search_id, Some("51239999".to_string()));
+        assert_eq!(results.query, "bàn phím");
+        assert_eq!(results.hits.len(), 2);
+        let page = core.get_search_page(results.search_id.clone().unwrap(), results.query.clone(), results.options.clone(), 2).await.unwrap();
+        assert_eq!(page.search_id, Some("51239999".to_string()));
+        // An expired search is run again by XenForo and redirected to a new id
+        let page = core.get_search_page(results.search_id.unwrap(), results.query, results.options, 3).await.unwrap();
+        assert_eq!(page.search_id, Some("51240000".to_string()));
+        assert_eq!(page.options.order, SearchOrder::Date);
+
+        let requests = server.await.unwrap();
+        assert!(requests[1].starts_with("POST /search/search "));
+        assert!(requests[2].starts_with("GET /search/51239999/?q="));
+        assert!(requests[3].starts_with("GET /search/51239999/?page=2&q=b%C3%A0n+ph%C3%ADm&t=post&o=date&c%5Bnodes%5D%5B%5D=33 "));
+        assert!(requests[5].starts_with("GET /search/51240000/?page=3"));
+        assert_eq!(requests.iter().filter(|r| r.starts_with("POST")).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_whats_new_older_activities() {
+        let (port, server) = mock_server(1, |_| ("200 OK", fs::read_to_string("resources/tests/latest_activity.html").unwrap())).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let result = core.get_whats_new(WhatsNewKind::LatestActivity, false, 2, Some("71234570".to_string())).await.unwrap();
+        let WhatsNew::Activities(activities) = result else { panic!("Expected activities") };
+        assert_eq!(activities.current_page, 2);
+        assert_eq!(activities.before_id, Some("71234560".to_string()));
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("GET /whats-new/latest-activity/?before_id=71234570 "));
+    }
+
+    #[tokio::test]
+    async fn test_whats_new_past_last_page() {
+        let (port, server) = mock_server(1, |_| ("200 OK", fs::read_to_string("resources/tests/watched_threads.html").unwrap())).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let result = core.get_whats_new(WhatsNewKind::Posts, false, 3, None).await.unwrap();
+        let WhatsNew::Threads(list) = result else { panic!("Expected threads") };
+        assert!(list.threads.is_empty());
+        assert_eq!(list.current_page, "3");
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /whats-new/posts/ "));
+    }
+
+    #[tokio::test]
+    async fn test_create_thread_invalid_prefix() {
+        let (port, server) = mock_server(1, |_| ("200 OK", fs::read_to_string("resources/tests/post_thread.html").unwrap())).await;
+
+        let core = VozCore::new(format!("http://127.0.0.1:{port}"));
+        let message = MessageContent::BBCode("Test".to_string());
+        let error = core.create_thread("33".to_string(), "Test".to_string(), Some("999".to_string()), message, ThreadOptions::default()).await.unwrap_err();
+        assert_eq!(error.code(), "invalid_argument");
+        assert_eq!(server.await.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_new_thread() -> Result<(), Box<dyn std::error::Error>> {
         let core = VozCore::new("voz.vn".to_string());